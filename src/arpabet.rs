@@ -0,0 +1,139 @@
+use std::fmt;
+
+// ARPABET
+//
+// ARPAbet is the phonetic notation of CMUdict and many other pronunciation
+// resources. Its phonemes are close to SAM's, but are written apart with
+// spaces, and the stress is a digit on each vowel: 0 for none, 1 for
+// primary and 2 for secondary stress.
+//
+//     HH AH0 L OW1   ->   /HAXLOW4
+//
+// Primary stress becomes SAM's ordinary stress 4 and secondary stress 5.
+// Unstressed AH and IH are the reduced vowels AX and IX. Besides the 39
+// phonemes of CMUdict, the extra symbols of the full ARPAbet (AX, IX, UX,
+// DX, EL, EM, EN, NX, Q, WH and AXR) are understood.
+
+/// SAM stress digit for ARPAbet stress 1.
+const PRIMARY_STRESS: char = '4';
+/// SAM stress digit for ARPAbet stress 2.
+const SECONDARY_STRESS: char = '5';
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArpabetError {
+    UnknownSymbol(String),
+    /// A stress digit other than 0, 1 or 2.
+    InvalidStress(String),
+}
+
+impl fmt::Display for ArpabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArpabetError::UnknownSymbol(symbol) => write!(f, "unknown ARPAbet symbol {}", symbol),
+            ArpabetError::InvalidStress(symbol) => write!(f, "invalid stress in {}", symbol),
+        }
+    }
+}
+
+impl std::error::Error for ArpabetError {}
+
+/// Convert ARPAbet symbols separated by spaces, e.g. "HH AH0 L OW1", to
+/// SAM phonetic input. Symbols may be lower case.
+pub fn arpabet_to_sam(arpabet: &str) -> Result<String, ArpabetError> {
+    let mut output = String::new();
+    for symbol in arpabet.split_whitespace() {
+        let upper = symbol.to_ascii_uppercase();
+        let (name, stress) = match upper.strip_suffix(|c: char| c.is_ascii_digit()) {
+            Some(name) => (name, upper[name.len()..].chars().next()),
+            None => (upper.as_str(), None),
+        };
+        let sam = match (name, stress) {
+            ("AH", Some('0')) => "AX",
+            ("IH", Some('0')) => "IX",
+            _ => sam_phoneme(name).ok_or_else(|| ArpabetError::UnknownSymbol(symbol.into()))?,
+        };
+        output.push_str(sam);
+        match stress {
+            None | Some('0') => {}
+            Some('1') => output.push(PRIMARY_STRESS),
+            Some('2') => output.push(SECONDARY_STRESS),
+            Some(_) => return Err(ArpabetError::InvalidStress(symbol.into())),
+        }
+    }
+    Ok(output)
+}
+
+/// SAM name of an ARPAbet phoneme, without stress.
+fn sam_phoneme(arpabet: &str) -> Option<&'static str> {
+    Some(match arpabet {
+        "AA" => "AA",
+        "AE" => "AE",
+        "AH" => "AH",
+        "AO" => "AO",
+        "AW" => "AW",
+        "AX" => "AX",
+        "AXR" | "ER" => "ER",
+        "AY" => "AY",
+        "B" => "B",
+        "CH" => "CH",
+        "D" => "D",
+        "DH" => "DH",
+        "DX" => "DX",
+        "EH" => "EH",
+        "EL" => "UL",
+        "EM" => "UM",
+        "EN" => "UN",
+        "EY" => "EY",
+        "F" => "F",
+        "G" => "G",
+        "HH" | "H" => "/H",
+        "IH" => "IH",
+        "IX" => "IX",
+        "IY" => "IY",
+        "JH" => "J",
+        "K" => "K",
+        "L" => "L",
+        "M" => "M",
+        "N" => "N",
+        "NG" | "NX" => "NX",
+        "OW" => "OW",
+        "OY" => "OY",
+        "P" => "P",
+        "Q" => "Q",
+        "R" => "R",
+        "S" => "S",
+        "SH" => "SH",
+        "T" => "T",
+        "TH" => "TH",
+        "UH" => "UH",
+        "UW" => "UW",
+        "UX" => "UX",
+        "V" => "V",
+        "W" => "W",
+        "WH" => "WH",
+        "Y" => "Y",
+        "Z" => "Z",
+        "ZH" => "ZH",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arpabet::{arpabet_to_sam, ArpabetError};
+
+    #[test]
+    fn converts_symbols_and_stress() {
+        assert_eq!(arpabet_to_sam("HH AH0 L OW1").unwrap(), "/HAXLOW4");
+        assert_eq!(arpabet_to_sam("s ih1 ng ih0 ng").unwrap(), "SIH4NXIXNX");
+        assert_eq!(arpabet_to_sam("EH2 JH ER0").unwrap(), "EH5JER");
+        assert_eq!(
+            arpabet_to_sam("K AA1 X"),
+            Err(ArpabetError::UnknownSymbol("X".into()))
+        );
+        assert_eq!(
+            arpabet_to_sam("AA3"),
+            Err(ArpabetError::InvalidStress("AA3".into()))
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    frames::{FormantTables, FrameVoice, SAMPLE_RATE, TICK_SAMPLES},
+    phonemes::{Control, Phonemes, MAX_PAUSE_MS},
+    Params,
+};
+
+// PARAMETER AUTOMATION
+//
+// `Params` sets the voice for a whole utterance. An `Automation` overrides
+// pitch, speed, mouth and throat with envelopes that change over the
+// utterance, e.g. a slow pitch glide, or a morph from one voice to another.
+//
+// An envelope is a list of points. Each point is anchored either to a
+// frame, or to the first frame of a phoneme in the converted phoneme list
+// (as printed by `print_phonemes`). Between two points the value is
+// interpolated linearly, and outside the points the nearest value is held.
+// Two points at the same position make a step.
+//
+// Inline controls in the phonetic input, e.g. "{pitch=50}", are applied
+// after the envelopes and override them from their phoneme on.
+
+/// Position in an utterance that an envelope point is anchored to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    Frame(usize),
+    Phoneme(usize),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Envelope {
+    points: Vec<(Anchor, u8)>,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a point, for building envelopes in one expression.
+    pub fn point(mut self, anchor: Anchor, value: u8) -> Self {
+        self.push(anchor, value);
+        self
+    }
+
+    pub fn push(&mut self, anchor: Anchor, value: u8) {
+        self.points.push((anchor, value));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Value for every frame of the phonemes, at least 256 of them, or
+    /// `None` if the envelope has no points.
+    fn resolve(&self, phoneme_starts: &[usize]) -> Option<Vec<u8>> {
+        if self.points.is_empty() {
+            return None;
+        }
+
+        let mut points = self
+            .points
+            .iter()
+            .map(|(anchor, value)| {
+                let frame = match *anchor {
+                    Anchor::Frame(frame) => frame,
+                    Anchor::Phoneme(idx) => phoneme_starts[idx.min(phoneme_starts.len() - 1)],
+                };
+                (frame, *value)
+            })
+            .collect::<Vec<_>>();
+        points.sort_by_key(|(frame, _)| *frame);
+
+        let frames = phoneme_starts[phoneme_starts.len() - 1].max(256);
+        let mut values = vec![0; frames];
+        for (frame, value) in values.iter_mut().enumerate() {
+            *value = match points.iter().rposition(|(f, _)| *f <= frame) {
+                None => points[0].1,
+                Some(i) if i + 1 == points.len() => points[i].1,
+                Some(i) => {
+                    let ((f0, v0), (f1, v1)) = (points[i], points[i + 1]);
+                    let t = (frame - f0) as f32 / (f1 - f0) as f32;
+                    (v0 as f32 + (v1 as f32 - v0 as f32) * t).round() as u8
+                }
+            };
+        }
+        Some(values)
+    }
+}
+
+/// Envelopes for the voice parameters. Parameters without points keep
+/// the value from `Params`.
+#[derive(Clone, Debug, Default)]
+pub struct Automation {
+    pub pitch: Envelope,
+    pub speed: Envelope,
+    pub mouth: Envelope,
+    pub throat: Envelope,
+}
+
+impl Automation {
+    /// Morph all parameters linearly from one voice to another between
+    /// two positions, e.g. from "SAM" to "Elf".
+    pub fn morph(from: &Params, to: &Params, start: Anchor, end: Anchor) -> Self {
+        let envelope = |from: u8, to: u8| Envelope::new().point(start, from).point(end, to);
+        Self {
+            pitch: envelope(from.pitch, to.pitch),
+            speed: envelope(from.speed, to.speed),
+            mouth: envelope(from.mouth, to.mouth),
+            throat: envelope(from.throat, to.throat),
+        }
+    }
+
+    pub(crate) fn resolve(&self, params: &Params, phonemes: &Phonemes) -> FrameVoices {
+        let mut phoneme_starts = vec![0];
+        for idx in 0..255 {
+            if phonemes.phoneme_index[idx] == 255 {
+                break;
+            }
+            phoneme_starts.push(phoneme_starts[idx] + phonemes.phoneme_length[idx] as usize);
+        }
+
+        let frames = phoneme_starts[phoneme_starts.len() - 1].max(256);
+        let resolve = |envelope: &Envelope, default: u8| {
+            envelope
+                .resolve(&phoneme_starts)
+                .unwrap_or_else(|| vec![default; frames])
+        };
+        let mouth = resolve(&self.mouth, params.mouth);
+        let throat = resolve(&self.throat, params.throat);
+
+        let mut keys: Vec<(u8, u8)> = Vec::new();
+        let mut formants = Vec::new();
+        let mut formant_idx = vec![0; frames];
+        for frame in 0..frames {
+            let key = (mouth[frame], throat[frame]);
+            formant_idx[frame] = match keys.iter().position(|k| *k == key) {
+                Some(idx) => idx,
+                None => {
+                    keys.push(key);
+                    formants.push(FormantTables::from_mouth_throat(key.0, key.1));
+                    formants.len() - 1
+                }
+            };
+        }
+
+        let mut voices = FrameVoices {
+            pitch: resolve(&self.pitch, params.pitch),
+            speed: resolve(&self.speed, params.speed),
+            formant_idx,
+            formants,
+        };
+        voices.apply_controls(phonemes, &phoneme_starts);
+        voices
+    }
+}
+
+/// Voice parameters resolved for every frame.
+pub(crate) struct FrameVoices {
+    pitch: Vec<u8>,
+    speed: Vec<u8>,
+    formant_idx: Vec<usize>,
+    formants: Vec<FormantTables>,
+}
+
+impl FrameVoices {
+    fn apply_controls(&mut self, phonemes: &Phonemes, phoneme_starts: &[usize]) {
+        let start = |idx: usize| phoneme_starts[idx.min(phoneme_starts.len() - 1)];
+        for (idx, control) in &phonemes.controls {
+            match *control {
+                Control::Pitch(pitch) => self.pitch[start(*idx)..].fill(pitch),
+                Control::Speed(speed) => self.speed[start(*idx)..].fill(speed),
+                Control::Pause(ms) => {
+                    // Spread the pause over its frames, whatever their number.
+                    let frames = start(idx + 1) - start(*idx);
+                    let ms = ms.min(MAX_PAUSE_MS);
+                    let ticks = ms as f64 * SAMPLE_RATE as f64 / 1000.0 / TICK_SAMPLES;
+                    let speed = (ticks / frames.max(1) as f64).round().clamp(1.0, 255.0) as u8;
+                    self.speed[start(*idx)..start(idx + 1)].fill(speed);
+                }
+                Control::Length(_) => {}
+            }
+        }
+    }
+
+    pub(crate) fn at(&self, frame: usize) -> FrameVoice<'_> {
+        FrameVoice {
+            pitch: self.pitch[frame],
+            speed: self.speed[frame],
+            formants: &self.formants[self.formant_idx[frame]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        automation::{Anchor, Automation, Envelope},
+        phonemes::convert_phonemes,
+        Params,
+    };
+
+    #[test]
+    fn interpolates_between_points() {
+        let envelope = Envelope::new()
+            .point(Anchor::Frame(10), 60)
+            .point(Anchor::Frame(20), 80);
+        let values = envelope.resolve(&[0]).unwrap();
+        assert_eq!(values[0], 60);
+        assert_eq!(values[10], 60);
+        assert_eq!(values[15], 70);
+        assert_eq!(values[20], 80);
+        assert_eq!(values[255], 80);
+    }
+
+    #[test]
+    fn phoneme_anchors_and_steps() {
+        // Phonemes starting at frames 0, 5 and 12.
+        let starts = [0, 5, 12, 20];
+        let envelope = Envelope::new()
+            .point(Anchor::Phoneme(1), 50)
+            .point(Anchor::Phoneme(2), 50)
+            .point(Anchor::Phoneme(2), 90);
+        let values = envelope.resolve(&starts).unwrap();
+        assert_eq!(values[4], 50);
+        assert_eq!(values[11], 50);
+        assert_eq!(values[12], 90);
+        assert!(Envelope::new().resolve(&starts).is_none());
+    }
+
+    #[test]
+    fn controls_after_the_frame_tables() {
+        // The third "AA" starts at frame 400.
+        let phonemes = convert_phonemes(b" AA{len=200} AA{len=200} {pitch=30}AA ");
+        let voices = Automation::default().resolve(&Params::default(), &phonemes);
+        assert_eq!(voices.at(300).pitch, 64);
+        assert_eq!(voices.at(400).pitch, 30);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::{
+    phoneme_rules::PhonemeRuleSet,
+    phonemes::{convert_phonemes_with, phoneme_name, read_phoneme, Phonemes},
+    reciter::{Origin, Reciter, Step},
+    segment::fits,
+};
+
+// EXPLANATION
+//
+// Why text is pronounced as it is, like the rules the C version prints
+// with `PrintRule` when debugging. The reciter reads the text in steps,
+// each with a rule, a word of the lexicon or as punctuation. Then
+// `convert_phonemes_with` inserts phonemes, and changes phonemes and their
+// lengths, with rules of its own such as "T R -> CH R". An `Explanation`
+// has both: the steps, and for every phoneme the step it comes from and
+// the rules that changed it.
+
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    pub phonemes: Phonemes,
+    /// For every phoneme, the step it comes from. The space at the end of
+    /// the phonetic input has none.
+    pub phoneme_steps: Vec<Option<usize>>,
+}
+
+/// Explain how `reciter` and the phoneme `rules` read text without markup.
+/// `None` if the text is too long to convert at once, see `segment::fits`.
+pub fn explain(reciter: &Reciter, rules: &PhonemeRuleSet, text: &str) -> Option<Explanation> {
+    let steps = reciter.explain(text);
+    let phonetic = format!(
+        "{} ",
+        steps
+            .iter()
+            .map(|step| step.phonemes.as_str())
+            .collect::<String>()
+    );
+    if !fits(&phonetic, rules) {
+        return None;
+    }
+
+    // The step of each phoneme of the phonetic input.
+    let mut input_steps = Vec::new();
+    for (idx, step) in steps.iter().enumerate() {
+        input_steps.extend(std::iter::repeat_n(idx, count_phonemes(&step.phonemes)));
+    }
+    let phonemes = convert_phonemes_with(phonetic.as_bytes(), rules);
+    let phoneme_steps = (0..phonemes.len())
+        .map(|idx| input_steps.get(phonemes.input_index[idx] as usize).copied())
+        .collect();
+    Some(Explanation {
+        steps,
+        phonemes,
+        phoneme_steps,
+    })
+}
+
+/// Phonemes in phonetic input without controls, as `parse_1` reads them.
+fn count_phonemes(phonetic: &str) -> usize {
+    let bytes = phonetic.as_bytes();
+    let mut count = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match read_phoneme(&bytes[idx..]) {
+            Some((_, length)) => {
+                idx += length;
+                count += 1;
+            }
+            None => idx += 1,
+        }
+    }
+    count
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "TEXT        PHONEMES    RULE")?;
+        for step in &self.steps {
+            let text = format!("\"{}\"", step.text);
+            let phonemes = format!("\"{}\"", step.phonemes);
+            match &step.origin {
+                Origin::Rule { rule, source, line } => writeln!(
+                    f,
+                    "{:11} {:11} \"{}\"  {}:{}",
+                    text, phonemes, rule, source, line
+                )?,
+                Origin::Lexicon => writeln!(f, "{:11} {:11} lexicon", text, phonemes)?,
+                Origin::Punctuation => writeln!(f, "{:11} {:11} punctuation", text, phonemes)?,
+            }
+        }
+        writeln!(f)?;
+
+        writeln!(f, "IDX  PHONEME  STRESS  LENGTH  TEXT        CHANGED BY")?;
+        let mut name = String::new();
+        for idx in 0..self.phonemes.len() {
+            let phoneme = self.phonemes.phoneme_index[idx];
+            if phoneme > 80 {
+                // Breath marks.
+                continue;
+            }
+            // The parts that the rules add to stops and affricates have no
+            // name of their own.
+            name = match phoneme_name(phoneme) {
+                Some(name) if name == " " => "' '".to_string(),
+                Some(name) => name,
+                None => format!("{}'", name),
+            };
+            let text = match self.phoneme_steps[idx] {
+                Some(step) => format!("\"{}\"", self.steps[step].text),
+                None => String::new(),
+            };
+            let rules = self
+                .phonemes
+                .rules
+                .iter()
+                .filter(|(i, _)| *i == idx)
+                .map(|(_, rule)| *rule)
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "{:3}  {:7}  {:6}  {:6}  {:11} {}",
+                idx,
+                name,
+                self.phonemes.stress[idx],
+                self.phonemes.phoneme_length[idx],
+                text,
+                rules.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        explain::explain,
+        phoneme_rules::PhonemeRuleSet,
+        reciter::{Origin, Reciter},
+    };
+
+    #[test]
+    fn train() {
+        let rules = PhonemeRuleSet::default();
+        let explanation = explain(&Reciter::default(), &rules, "train").unwrap();
+        let ch = (0..explanation.phonemes.len())
+            .find(|idx| explanation.phonemes.rules.contains(&(*idx, "T R -> CH R")))
+            .unwrap();
+        let step = &explanation.steps[explanation.phoneme_steps[ch].unwrap()];
+        assert_eq!(step.text, "T");
+        assert!(matches!(
+            &step.origin,
+            Origin::Rule { source, .. } if source == "english.rules"
+        ));
+        assert!(explain(&Reciter::default(), &rules, &"word ".repeat(100)).is_none());
+    }
+}
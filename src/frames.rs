@@ -1,5 +1,10 @@
-use crate::tables::{RECTANGLE, SAMPLE_TABLE, SINE};
-use crate::{phonemes::Phonemes, Params};
+use crate::tables::{RECTANGLE, SAMPLE_OFF_LEVEL, SAMPLE_TABLE, SINE};
+use crate::{
+    automation::Automation,
+    intonation::Inflection,
+    phonemes::{Control, Phonemes},
+    Params,
+};
 use std::cmp::Ordering;
 
 pub fn print_frames(frames: &FramesTables) {
@@ -52,6 +57,10 @@ impl Default for FormantTables {
 
 impl FormantTables {
     pub fn from_params(params: &Params) -> Self {
+        Self::from_mouth_throat(params.mouth, params.throat)
+    }
+
+    pub fn from_mouth_throat(mouth: u8, throat: u8) -> Self {
         let mut tables = FormantTables::default();
         let mouth_formants5_29: [u8; 30] = [
             0, 0, 0, 0, 0, 10, 14, 19, 24, 27, 23, 21, 16, 20, 14, 18, 14, 18, 18, 16, 13, 15, 11,
@@ -66,24 +75,17 @@ impl FormantTables {
 
         for idx in 5..30 {
             if mouth_formants5_29[idx] != 0 {
-                tables.mouth[idx] = trans(params.mouth, mouth_formants5_29[idx]);
+                tables.mouth[idx] = trans(mouth, mouth_formants5_29[idx]);
             }
-            println!(
-                "Set mouth idx={idx} table[idx]={} mouth={} other={} value={}",
-                mouth_formants5_29[idx],
-                params.mouth,
-                mouth_formants5_29[idx] as u32,
-                tables.mouth[idx]
-            );
 
             if throat_formants5_29[idx] != 0 {
-                tables.throat[idx] = trans(params.throat, throat_formants5_29[idx]);
+                tables.throat[idx] = trans(throat, throat_formants5_29[idx]);
             }
         }
 
         for (idx, table_idx) in (48..54).enumerate() {
-            tables.mouth[table_idx] = trans(params.mouth, mouth_formants48_53[idx]);
-            tables.throat[table_idx] = trans(params.throat, throat_formants48_53[idx]);
+            tables.mouth[table_idx] = trans(mouth, mouth_formants48_53[idx]);
+            tables.throat[table_idx] = trans(throat, throat_formants48_53[idx]);
         }
 
         tables
@@ -126,15 +128,23 @@ fn trans(mut a: u8, b: u8) -> u8 {
 }
 
 pub struct FramesTables {
-    pitches: [u8; 256],
-    frequency1: [u8; 256],
-    frequency2: [u8; 256],
-    frequency3: [u8; 256],
-    amplitude1: [u8; 256],
-    amplitude2: [u8; 256],
-    amplitude3: [u8; 256],
-    sampled_consonant_flag: [u8; 256],
-    sum_length: u8,
+    pub(crate) pitches: [u8; 256],
+    pub(crate) frequency1: [u8; 256],
+    pub(crate) frequency2: [u8; 256],
+    pub(crate) frequency3: [u8; 256],
+    pub(crate) amplitude1: [u8; 256],
+    pub(crate) amplitude2: [u8; 256],
+    pub(crate) amplitude3: [u8; 256],
+    pub(crate) sampled_consonant_flag: [u8; 256],
+    pub(crate) speeds: [u8; 256],
+    pub(crate) sum_length: u8,
+}
+
+/// Voice parameters that apply to a single frame.
+pub(crate) struct FrameVoice<'a> {
+    pub pitch: u8,
+    pub speed: u8,
+    pub formants: &'a FormantTables,
 }
 
 // RENDER THE PHONEMES IN THE LIST
@@ -152,7 +162,54 @@ pub struct FramesTables {
 // 4. Render the each frame.
 
 //void Code47574()
-pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables) -> FramesTables {
+/// The frames of each breath group of `phonemes`, see `breath_groups`.
+pub fn mk_frames(
+    params: &Params,
+    phonemes: &Phonemes,
+    formants: &FormantTables,
+) -> Vec<FramesTables> {
+    mk_frames_with(params, phonemes, |_| FrameVoice {
+        pitch: params.pitch,
+        speed: params.speed,
+        formants,
+    })
+}
+
+/// Like `mk_frames`, but pitch, speed, mouth and throat follow the envelopes
+/// in `automation` where they are set, and `params` elsewhere. The formant
+/// tables are recomputed for every frame with a different mouth or throat.
+pub fn mk_frames_automated(
+    params: &Params,
+    phonemes: &Phonemes,
+    automation: &Automation,
+) -> Vec<FramesTables> {
+    let voices = automation.resolve(params, phonemes);
+    mk_frames_with(params, phonemes, |frame_idx| voices.at(frame_idx))
+}
+
+/// `voice_at` is called with the frame counted from the start of the
+/// utterance, not of the breath group.
+fn mk_frames_with<'a>(
+    params: &Params,
+    phonemes: &Phonemes,
+    voice_at: impl Fn(usize) -> FrameVoice<'a>,
+) -> Vec<FramesTables> {
+    let mut start = 0;
+    breath_groups(phonemes)
+        .iter()
+        .map(|group| {
+            let frames = mk_group_frames(params, group, |frame_idx| voice_at(start + frame_idx));
+            start += frames.sum_length as usize;
+            frames
+        })
+        .collect()
+}
+
+fn mk_group_frames<'a>(
+    params: &Params,
+    phonemes: &Phonemes,
+    voice_at: impl Fn(usize) -> FrameVoice<'a>,
+) -> FramesTables {
     // CREATE FRAMES
     //
     // The length parameter in the list corresponds to the number of frames
@@ -171,6 +228,7 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
         amplitude2: [0; 256],
         amplitude3: [0; 256],
         sampled_consonant_flag: [0; 256],
+        speeds: [params.speed; 256],
     };
 
     const SAMPLED_CONSONANT_FLAGS: [u8; 80] = [
@@ -216,25 +274,21 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
             if phoneme == 255 {
                 break;
             }
-            println!("converting phoneme {} at {} to frames.", phoneme, idx);
 
             if phoneme == 1 {
                 // Period
-                add_inflection(frame_idx, &mut frames, 1);
+                add_inflection(frame_idx, &mut frames, &params.intonation.statement);
             }
 
             if phoneme == 2 {
                 // Question mark
-                add_inflection(frame_idx, &mut frames, 255);
+                add_inflection(frame_idx, &mut frames, &params.intonation.question);
             }
 
-            let table: [u8; 11] = [0, 0, 0xE0, 0xE6, 0xEC, 0xF3, 0xF9, 0, 6, 0xC, 6];
-            phase_1 = table[phonemes.stress[idx] as usize + 1];
+            phase_1 = params.intonation.stress_offset(phonemes.stress[idx]);
             for _ in 0..phonemes.phoneme_length[idx] {
-                println!(
-                    "freq1[{}]=freq1data[{}]={}",
-                    frame_idx, phoneme, formants.mouth[phoneme as usize]
-                );
+                let voice = voice_at(frame_idx);
+                let formants = voice.formants;
                 frames.frequency1[frame_idx] = formants.mouth[phoneme as usize];
                 frames.frequency2[frame_idx] = formants.throat[phoneme as usize];
                 frames.frequency3[frame_idx] = FREQ3[phoneme as usize];
@@ -243,15 +297,12 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
                 frames.amplitude3[frame_idx] = AMPLITUDE3[phoneme as usize];
                 frames.sampled_consonant_flag[frame_idx] =
                     SAMPLED_CONSONANT_FLAGS[phoneme as usize];
-                frames.pitches[frame_idx] = params.pitch + phase_1;
+                frames.pitches[frame_idx] = voice.pitch.wrapping_add_signed(phase_1);
+                frames.speeds[frame_idx] = voice.speed;
                 frame_idx += 1;
             }
-
-            println!("{} frames", frame_idx);
         }
     }
-    println!("BEFORE TRANSITIONS");
-    print_frames(&frames);
 
     // CREATE TRANSITIONS
     //
@@ -387,10 +438,9 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
     loop {
         let phoneme = phonemes.phoneme_index[idx];
         let next_phoneme = phonemes.phoneme_index[idx + 1];
-        println!("phoneme and next {} {}\n", phoneme, next_phoneme);
-        frames.sum_length += phonemes.phoneme_length[idx];
+        frames.sum_length = frames.sum_length.wrapping_add(phonemes.phoneme_length[idx]);
 
-        if phoneme == 255 {
+        if phoneme == 255 || next_phoneme == 255 {
             break;
         }
 
@@ -421,80 +471,49 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
         );
         let (phase_1, phase_2) = match r1.cmp(&r2) {
             Ordering::Less => (
-                IN_BLEND_LENGTH[phoneme as usize],
-                OUT_BLEND_LENGTH[phoneme as usize],
+                IN_BLEND_LENGTH[next_phoneme as usize],
+                OUT_BLEND_LENGTH[next_phoneme as usize],
             ),
             Ordering::Equal => (
                 OUT_BLEND_LENGTH[phoneme as usize],
                 OUT_BLEND_LENGTH[next_phoneme as usize],
             ),
             Ordering::Greater => (
-                OUT_BLEND_LENGTH[next_phoneme as usize],
-                IN_BLEND_LENGTH[next_phoneme as usize],
+                OUT_BLEND_LENGTH[phoneme as usize],
+                IN_BLEND_LENGTH[phoneme as usize],
             ),
         };
 
         let start_frame = frames.sum_length.wrapping_sub(phase_1);
         let end_frame = frames.sum_length.wrapping_add(phase_2);
+        let transition = phase_1.wrapping_add(phase_2);
+
+        if transition.wrapping_sub(2) & 128 == 0 {
+            // Unlike the other values, the pitch goes from the middle of
+            // the current phoneme to the middle of the next.
+            let curr_halfwidth = phonemes.phoneme_length[idx] >> 1;
+            let next_halfwidth = phonemes.phoneme_length[idx + 1] >> 1;
+            let center_next = next_halfwidth.wrapping_add(frames.sum_length);
+            let center_curr = frames.sum_length.wrapping_sub(curr_halfwidth);
+            let change = frames.pitches[center_next as usize]
+                .wrapping_sub(frames.pitches[center_curr as usize]);
+            interpolate(
+                &mut frames.pitches,
+                curr_halfwidth.wrapping_add(next_halfwidth),
+                start_frame,
+                change,
+            );
 
-        // ???
-        let do_interpolation = (phase_1.wrapping_add(phase_2).wrapping_sub(2)) & 128 == 0;
-
-        if do_interpolation {
-            for (is_pitch, current_table) in [
-                (true, &mut frames.pitches),
-                (false, &mut frames.frequency1),
-                (false, &mut frames.frequency2),
-                (false, &mut frames.frequency3),
-                (false, &mut frames.amplitude1),
-                (false, &mut frames.amplitude2),
-                (false, &mut frames.amplitude3),
+            for table in [
+                &mut frames.frequency1,
+                &mut frames.frequency2,
+                &mut frames.frequency3,
+                &mut frames.amplitude1,
+                &mut frames.amplitude2,
+                &mut frames.amplitude3,
             ] {
-                let (value, length) = if is_pitch {
-                    let curr_halfwidth = phonemes.phoneme_length[idx] >> 1;
-                    let next_halfwidth = phonemes.phoneme_length[idx + 1] >> 1;
-                    let center_next = next_halfwidth + frames.sum_length;
-                    let center_curr = frames.sum_length - curr_halfwidth;
-
-                    let value =
-                        current_table[center_next as usize] - current_table[center_curr as usize];
-                    let length = curr_halfwidth + next_halfwidth;
-                    (value, length)
-                } else {
-                    let value =
-                        current_table[end_frame as usize] - current_table[start_frame as usize];
-                    let length = phase_1 + phase_2;
-                    (value, length)
-                };
-
-                let value_sign = value & 128;
-
-                // Change per frame
-                let (change_remainder, change_per_frame) = {
-                    let m53 = value as i8;
-                    let m53abs = m53.abs() as u8;
-                    let change_remainder = m53abs % length;
-                    let change_per_frame = (m53 / length as i8) as u8;
-                    (change_remainder, change_per_frame)
-                };
-
-                let mut carry = 0;
-                for idx in start_frame..(start_frame + length) {
-                    let mut set_value = current_table[idx as usize] + change_per_frame;
-                    carry += change_remainder;
-                    if carry >= length {
-                        carry -= length;
-                        if value_sign % 128 == 0 {
-                            if set_value != 0 {
-                                set_value += 1;
-                            }
-                        } else {
-                            set_value -= 1;
-                        }
-                    }
-
-                    current_table[idx as usize] = set_value;
-                }
+                let change = table[end_frame as usize].wrapping_sub(table[start_frame as usize]);
+                interpolate(table, transition, start_frame, change);
             }
         }
         idx += 1;
@@ -507,10 +526,23 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
     // pitch level (monotone).
     if !params.singmode {
         for i in 0..256 {
-            frames.pitches[i] -= frames.frequency1[i] >> 1;
+            frames.pitches[i] = frames.pitches[i].wrapping_sub(frames.frequency1[i] >> 1);
         }
     }
 
+    // PINNED PITCHES
+    //
+    // Set last, so that nothing changes them.
+    for (pin_idx, pitch) in &phonemes.pitch_pins {
+        let start = phonemes.phoneme_length[..*pin_idx]
+            .iter()
+            .map(|length| *length as usize)
+            .sum::<usize>()
+            .min(256);
+        let end = (start + phonemes.phoneme_length[*pin_idx] as usize).min(256);
+        frames.pitches[start..end].fill(*pitch);
+    }
+
     // RESCALE AMPLITUDE
     //
     // Rescale volume from a linear scale to decibels.
@@ -528,7 +560,108 @@ pub fn mk_frames(params: &Params, phonemes: &Phonemes, formants: &FormantTables)
     frames
 }
 
-pub fn mk_wav(frames: &FramesTables) -> Vec<u8> {
+/// Change `table` linearly by `change` over the `width` frames after
+/// `frame`, like `interpolate` in the C version. Values and frames wrap
+/// around like its bytes.
+fn interpolate(table: &mut [u8; 256], width: u8, frame: u8, change: u8) {
+    if width == 0 {
+        // Two phonemes of less than two frames.
+        return;
+    }
+    let change = change as i8;
+    let remainder = change.unsigned_abs() % width;
+    let per_frame = (change as i32 / width as i32) as u8;
+
+    let mut error: u8 = 0;
+    let mut frame = frame;
+    let mut value = table[frame as usize].wrapping_add(per_frame);
+    for _ in 1..width {
+        error = error.wrapping_add(remainder);
+        if error >= width {
+            // A whole step of error, so adjust the value.
+            error -= width;
+            if change < 0 {
+                value = value.wrapping_sub(1);
+            } else if value != 0 {
+                value = value.wrapping_add(1);
+            }
+        }
+        frame = frame.wrapping_add(1);
+        table[frame as usize] = value;
+        value = value.wrapping_add(per_frame);
+    }
+}
+
+/// Split the phonemes of `convert_phonemes` at its breath marks (254), and
+/// drop the spaces, like `PrepareOutput` of the C version, which renders
+/// the phonemes between two marks at a time. Pauses from controls are kept. A group longer than 255 frames, which the C version
+/// wraps around in its tables, is split before the phoneme that would
+/// overflow them.
+pub(crate) fn breath_groups(phonemes: &Phonemes) -> Vec<Phonemes> {
+    let mut groups = Vec::new();
+    let mut group = Phonemes::default();
+    let mut group_idx = 0;
+    let mut group_frames = 0;
+    for idx in 0..256 {
+        let phoneme = phonemes.phoneme_index[idx];
+        let length = phonemes.phoneme_length[idx];
+        let overflows = group_frames + length as usize > 255;
+        if phoneme == 255 || phoneme == 254 || overflows {
+            group.phoneme_index[group_idx] = 255;
+            if group_idx != 0 {
+                groups.push(group);
+            }
+            group = Phonemes::default();
+            group_idx = 0;
+            group_frames = 0;
+        }
+        if phoneme == 255 {
+            break;
+        }
+        let is_pause = phonemes
+            .controls
+            .iter()
+            .any(|(i, control)| *i == idx && matches!(control, Control::Pause(_)));
+        if phoneme == 254 || (phoneme == 0 && !is_pause) {
+            continue;
+        }
+        group.phoneme_index[group_idx] = phoneme;
+        group.phoneme_length[group_idx] = length;
+        group.stress[group_idx] = phonemes.stress[idx];
+        for (pin_idx, pitch) in &phonemes.pitch_pins {
+            if *pin_idx == idx {
+                group.pitch_pins.push((group_idx, *pitch));
+            }
+        }
+        group_idx += 1;
+        group_frames += length as usize;
+    }
+    groups
+}
+
+/// Sample rate of the buffer produced by `mk_wav`, same as the C version.
+pub const SAMPLE_RATE: u32 = 22050;
+
+/// Duration of one pass through the render loop of `mk_wav`, measured in
+/// samples at `SAMPLE_RATE`. The loop advances the output position by
+/// 162/50 samples for every 5 samples it writes. A frame lasts `speed`
+/// ticks and a glottal pulse lasts `pitch` ticks.
+pub const TICK_SAMPLES: f64 = 162.0 / 50.0;
+
+/// Render the frames of each breath group one after the other, like the
+/// calls to `Render` of the C version. The buffer is as long as in the C
+/// version, without the samples written in advance.
+pub fn mk_wav(groups: &[FramesTables]) -> Vec<u8> {
+    let mut output_buffer = C64SoundBuffer::default();
+    for frames in groups {
+        render_frames(frames, &mut output_buffer);
+    }
+    let mut buffer = output_buffer.buffer;
+    buffer.truncate(output_buffer.idx / 50);
+    buffer
+}
+
+fn render_frames(frames: &FramesTables, output_buffer: &mut C64SoundBuffer) {
     // PROCESS THE FRAMES
     //
     // In traditional vocal synthesis, the glottal pulse drives filters, which
@@ -537,82 +670,92 @@ pub fn mk_wav(frames: &FramesTables) -> Vec<u8> {
     // SAM generates these formants directly with sin and rectangular waves.
     // To simulate them being driven by the glottal pulse, the waveforms are
     // reset at the beginning of each glottal pulse.
-    let mut output_buffer = C64SoundBuffer::default();
-
-    {
-        let mut frame_idx = 0;
-        let mut phase_1 = 0;
-        let mut phase_2 = 0;
-        let mut phase_3 = 0;
-        let mut sum_length = frames.sum_length;
-        let mut speed_counter = 72;
-        let mut glottal_pulse_length = frames.pitches[frame_idx];
-        let mut voiced_length = glottal_pulse_length - (glottal_pulse_length >> 2);
-        loop {
-            let consonant_flag = frames.sampled_consonant_flag[frame_idx];
-            if consonant_flag & 248 != 0 {
-                render_sample(frame_idx, frames, &mut output_buffer);
-                frame_idx += 2;
-                sum_length -= 2;
-            } else {
-                let mut ary = [0u8; 5];
-                let mut p1 = phase_1 as i32 * 256;
-                let mut p2 = phase_2 as i32 * 256;
-                let mut p3 = phase_3 as i32 * 256;
-                for item in &mut ary {
-                    let sp1: i8 = SINE[(0xff & (p1 >> 8)) as usize];
-                    let sp2: i8 = SINE[(0xff & (p2 >> 8)) as usize];
-                    let rp3: i8 = RECTANGLE[(0xff & (p3 >> 8)) as usize] as i8;
-
-                    let sin1 = sp1 as i32 * (frames.amplitude1[frame_idx] as i32 & 0x0f);
-                    let sin2 = sp2 as i32 * (frames.amplitude2[frame_idx] as i32 & 0x0f);
-                    let rect = rp3 as i32 * (frames.amplitude3[frame_idx] as i32 & 0x0f);
-                    let sum = sin1 + sin2 + rect;
-                    let mux = (sum / 32) + 128;
-                    *item = mux as u8;
-
-                    p1 += frames.frequency1[frame_idx] as i32 * 256 / 4;
-                    p2 += frames.frequency2[frame_idx] as i32 * 256 / 4;
-                    p3 += frames.frequency3[frame_idx] as i32 * 256 / 4;
-                }
+    let mut frame_idx = 0;
+    let mut phase_1: u8 = 0;
+    let mut phase_2: u8 = 0;
+    let mut phase_3: u8 = 0;
+    let mut sample_offset = 0;
+    let mut sum_length = frames.sum_length;
+    let mut speed_counter = frames.speeds[frame_idx];
+    let mut glottal_pulse_length = frames.pitches[frame_idx];
+    let mut voiced_length = glottal_pulse_length - (glottal_pulse_length >> 2);
+    while sum_length != 0 {
+        let consonant_flag = frames.sampled_consonant_flag[frame_idx];
+        if consonant_flag & 248 != 0 {
+            // Unvoiced sampled phoneme, rendered for two frames.
+            render_sample(
+                frame_idx,
+                consonant_flag,
+                frames,
+                &mut sample_offset,
+                output_buffer,
+            );
+            frame_idx += 2;
+            sum_length = sum_length.saturating_sub(2);
+            speed_counter = frames.speeds[frame_idx.min(255)];
+        } else {
+            let mut ary = [0u8; 5];
+            let mut p1 = phase_1 as i32 * 256;
+            let mut p2 = phase_2 as i32 * 256;
+            let mut p3 = phase_3 as i32 * 256;
+            for item in &mut ary {
+                let sp1: i8 = SINE[(0xff & (p1 >> 8)) as usize];
+                let sp2: i8 = SINE[(0xff & (p2 >> 8)) as usize];
+                let rp3: i8 = RECTANGLE[(0xff & (p3 >> 8)) as usize] as i8;
+
+                let sin1 = sp1 as i32 * (frames.amplitude1[frame_idx] as i32 & 0x0f);
+                let sin2 = sp2 as i32 * (frames.amplitude2[frame_idx] as i32 & 0x0f);
+                let rect = rp3 as i32 * (frames.amplitude3[frame_idx] as i32 & 0x0f);
+                let sum = sin1 + sin2 + rect;
+                let mux = (sum / 32) + 128;
+                *item = mux as u8;
+
+                p1 += frames.frequency1[frame_idx] as i32 * 256 / 4;
+                p2 += frames.frequency2[frame_idx] as i32 * 256 / 4;
+                p3 += frames.frequency3[frame_idx] as i32 * 256 / 4;
+            }
 
-                output_buffer.output_5(0, &ary);
-                speed_counter -= 1;
-                if speed_counter != 0 {}
+            output_buffer.output_5(0, &ary);
 
+            // Each frame lasts for `speed` passes through the loop.
+            speed_counter = speed_counter.wrapping_sub(1);
+            if speed_counter == 0 {
                 frame_idx += 1;
                 sum_length -= 1;
+                if sum_length == 0 {
+                    break;
+                }
+                speed_counter = frames.speeds[frame_idx];
             }
 
-            if sum_length == 0 {
-                return output_buffer.buffer;
-            }
-
-            if glottal_pulse_length == 0 {
-                glottal_pulse_length = frames.pitches[frame_idx];
-                voiced_length = glottal_pulse_length - (glottal_pulse_length >> 2);
-                phase_1 = 0;
-                phase_2 = 0;
-                phase_3 = 0;
-                continue;
-            }
-
-            voiced_length -= 1;
-            if voiced_length != 0 && consonant_flag == 0 {
-                phase_1 += frames.frequency1[frame_idx];
-                phase_2 += frames.frequency2[frame_idx];
-                phase_3 += frames.frequency3[frame_idx];
-                continue;
+            glottal_pulse_length = glottal_pulse_length.wrapping_sub(1);
+            if glottal_pulse_length != 0 {
+                // Within the first 75% of the glottal pulse the formants
+                // carry on. Voiced sampled phonemes put their sample in
+                // the rest of it.
+                voiced_length = voiced_length.wrapping_sub(1);
+                if voiced_length != 0 || consonant_flag == 0 {
+                    phase_1 = phase_1.wrapping_add(frames.frequency1[frame_idx]);
+                    phase_2 = phase_2.wrapping_add(frames.frequency2[frame_idx]);
+                    phase_3 = phase_3.wrapping_add(frames.frequency3[frame_idx]);
+                    continue;
+                }
+                render_sample(
+                    frame_idx,
+                    consonant_flag,
+                    frames,
+                    &mut sample_offset,
+                    output_buffer,
+                );
             }
-
-            render_sample(frame_idx, frames, &mut output_buffer);
-            glottal_pulse_length = frames.pitches[frame_idx];
-            voiced_length = glottal_pulse_length - (glottal_pulse_length >> 2);
-            phase_1 = 0;
-            phase_2 = 0;
-            phase_3 = 0;
-            continue;
         }
+
+        // Start the next glottal pulse, with the formants in phase with it.
+        glottal_pulse_length = frames.pitches[frame_idx.min(255)];
+        voiced_length = glottal_pulse_length - (glottal_pulse_length >> 2);
+        phase_1 = 0;
+        phase_2 = 0;
+        phase_3 = 0;
     }
 }
 
@@ -641,15 +784,19 @@ const C64_TIMETABLE: [[u8; 5]; 5] = [
     [199, 0, 0, 54, 54],
 ];
 
-fn add_inflection(frame_idx: usize, frames: &mut FramesTables, inflection_direction: u8) {
-    let mut idx = frame_idx.saturating_sub(30);
+fn add_inflection(frame_idx: usize, frames: &mut FramesTables, inflection: &Inflection) {
+    // Punctuation at the first frame has nothing before it to inflect.
+    if inflection.frames == 0 || inflection.slope == 0 || frame_idx == 0 {
+        return;
+    }
+    let mut idx = frame_idx.saturating_sub(inflection.frames as usize);
     while frames.pitches[idx] == 127 {
         idx += 1;
     }
 
     let mut pitch = frames.pitches[idx];
     loop {
-        pitch = pitch.wrapping_add(inflection_direction);
+        pitch = pitch.wrapping_add_signed(inflection.slope);
 
         frames.pitches[idx] = pitch;
         loop {
@@ -664,51 +811,58 @@ fn add_inflection(frame_idx: usize, frames: &mut FramesTables, inflection_direct
     }
 }
 
-fn render_sample(frame_idx: usize, frames: &FramesTables, output_buffer: &mut C64SoundBuffer) {
-    let consonant_flag = frames.sampled_consonant_flag[frame_idx];
-    let mem56 = (consonant_flag & 7) - 1;
-
-    const TABLE: [u8; 5] = [0x18, 0x1A, 0x17, 0x17, 0x17];
-
-    let table_value = TABLE[mem56 as usize];
-    let mem47 = mem56;
+/// Render the sample of a sampled consonant, like `RenderSample` of the C
+/// version. `consonant_flag` is read where the pass through the render
+/// loop started, before it moved on to the next frame. Voiced consonants
+/// carry on in their sample from `offset`.
+fn render_sample(
+    frame_idx: usize,
+    consonant_flag: u8,
+    frames: &FramesTables,
+    offset: &mut u8,
+    output_buffer: &mut C64SoundBuffer,
+) {
+    // T, S, Z / CH, J, SH, ZH / P, F, V, TH, DH / /H / /X
+    let sample_idx = ((consonant_flag & 7).wrapping_sub(1) as usize).min(4);
+    let samples = &SAMPLE_TABLE[sample_idx * 256..][..256];
 
     if consonant_flag & 248 == 0 {
-        // VOICED
-
-        let pitch = frames.pitches[frame_idx] >> 4;
-
-        let mut phase1 = pitch ^ 255;
-        let mut sample_table_idx = 0;
-        while phase1 != 0 {
-            let sample = SAMPLE_TABLE[mem47 as usize * 256 + sample_table_idx];
-
-            for _mem56 in [8, 7, 6, 5, 4, 3, 2, 1] {
-                if (sample << 1) & 128 != 0 {
+        // VOICED: Z*, ZH, V*, DH
+        let mut phase = (frames.pitches[frame_idx] >> 4) ^ 255;
+        loop {
+            let mut sample = samples[*offset as usize];
+            for _ in 0..8 {
+                if sample & 128 != 0 {
                     output_buffer.output_5(3, &[(26 & 0xf) * 16; 5]);
                 } else {
                     output_buffer.output_5(4, &[(6 & 0xf) * 16; 5]);
                 }
+                sample <<= 1;
+            }
+            *offset = offset.wrapping_add(1);
+            phase = phase.wrapping_add(1);
+            if phase == 0 {
+                break;
             }
-
-            phase1 = phase1.wrapping_add(1);
-            sample_table_idx += 1;
         }
     } else {
         // UNVOICED
+        let level = SAMPLE_OFF_LEVEL[sample_idx];
         let mut offset = (consonant_flag & 248) ^ 255;
-        while offset != 0 {
-            for _mem56 in [8, 7, 6, 5, 4, 3, 2, 1] {
-                let sample = SAMPLE_TABLE[mem47 as usize * 256 + offset as usize];
-                if (sample << 1) & 128 == 0 {
-                    output_buffer.output_5(1, &[table_value & 0x0f; 5]);
-                }
-
-                if (sample << 1) & 128 != 0 || table_value != 0 {
+        loop {
+            let mut sample = samples[offset as usize];
+            for _ in 0..8 {
+                if sample & 128 != 0 {
                     output_buffer.output_5(2, &[5 * 16; 5]);
+                } else {
+                    output_buffer.output_5(1, &[(level & 0xf) * 16; 5]);
                 }
+                sample <<= 1;
             }
             offset = offset.wrapping_add(1);
+            if offset == 0 {
+                break;
+            }
         }
     }
 }
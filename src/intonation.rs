@@ -0,0 +1,160 @@
+use std::{fmt, str::FromStr};
+
+// INTONATION
+//
+// `mk_frames` adds an offset to the pitch of every frame of a phoneme by
+// its stress, from the -32 units of stress 1 to the +12 of stress 8. A
+// consonant before a stressed vowel gets the stress after it, up to 9.
+//
+// Before a "." the pitch of the last 30 frames is bent one unit per frame
+// down, and before a "?" up. The pitch value is the length of a glottal
+// pulse, so adding to it lowers the voice, see `pitch`: a statement falls
+// with a positive slope and a question rises with a negative one.
+//
+// An `IntonationModel` holds the offsets and both inflections, and is part
+// of the `Params` of a voice. `IntonationPreset::Sam` is the model of the C
+// version and the default.
+
+/// Bends the pitch of the frames before a punctuation mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Inflection {
+    /// Frames before the punctuation mark that are bent.
+    pub frames: u8,
+    /// Pitch units added per frame. Positive slopes fall, negative ones
+    /// rise, and a slope of 0 leaves the pitch alone.
+    pub slope: i8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntonationModel {
+    /// Pitch units added to the frames of a phoneme, by stress 0 to 9.
+    pub stress_pitch: [i8; 10],
+    /// Inflection before a ".".
+    pub statement: Inflection,
+    /// Inflection before a "?".
+    pub question: Inflection,
+}
+
+impl Default for IntonationModel {
+    fn default() -> Self {
+        IntonationPreset::Sam.model()
+    }
+}
+
+impl IntonationModel {
+    /// Pitch offset of a stress, none for stresses out of the table.
+    pub(crate) fn stress_offset(&self, stress: u8) -> i8 {
+        self.stress_pitch.get(stress as usize).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntonationPreset {
+    /// No offsets by stress and no inflections, a monotone apart from the
+    /// contour of the first formant.
+    Flat,
+    /// The tables of the C version.
+    Sam,
+    /// Wider offsets by stress, and longer inflections that fall and rise
+    /// further.
+    Expressive,
+}
+
+impl IntonationPreset {
+    pub const ALL: [IntonationPreset; 3] = [
+        IntonationPreset::Flat,
+        IntonationPreset::Sam,
+        IntonationPreset::Expressive,
+    ];
+
+    /// Name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntonationPreset::Flat => "flat",
+            IntonationPreset::Sam => "sam",
+            IntonationPreset::Expressive => "expressive",
+        }
+    }
+
+    pub fn model(&self) -> IntonationModel {
+        let (stress_pitch, statement, question) = match self {
+            IntonationPreset::Flat => ([0; 10], (0, 0), (0, 0)),
+            IntonationPreset::Sam => ([0, -32, -26, -20, -13, -7, 0, 6, 12, 6], (30, 1), (30, -1)),
+            IntonationPreset::Expressive => {
+                ([0, -40, -32, -24, -16, -8, 0, 8, 16, 8], (45, 1), (40, -1))
+            }
+        };
+
+        IntonationModel {
+            stress_pitch,
+            statement: Inflection {
+                frames: statement.0,
+                slope: statement.1,
+            },
+            question: Inflection {
+                frames: question.0,
+                slope: question.1,
+            },
+        }
+    }
+}
+
+impl fmt::Display for IntonationPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<IntonationPreset> for IntonationModel {
+    fn from(preset: IntonationPreset) -> Self {
+        preset.model()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownIntonation(pub String);
+
+impl fmt::Display for UnknownIntonation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown intonation \"{}\", expected one of:", self.0)?;
+        for preset in IntonationPreset::ALL {
+            write!(f, " {}", preset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownIntonation {}
+
+impl FromStr for IntonationPreset {
+    type Err = UnknownIntonation;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        IntonationPreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == normalized)
+            .ok_or_else(|| UnknownIntonation(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intonation::{IntonationModel, IntonationPreset};
+
+    #[test]
+    fn sam_is_the_c_version() {
+        // Indexed by stress + 1 in the C version.
+        let table: [u8; 11] = [0, 0, 0xE0, 0xE6, 0xEC, 0xF3, 0xF9, 0, 6, 0xC, 6];
+        let model = IntonationModel::default();
+        for stress in 0..10 {
+            assert_eq!(
+                model.stress_offset(stress) as u8,
+                table[stress as usize + 1]
+            );
+        }
+        assert_eq!(model, IntonationPreset::Sam.model());
+        assert_eq!("Expressive".parse(), Ok(IntonationPreset::Expressive));
+        assert!("monotone".parse::<IntonationPreset>().is_err());
+    }
+}
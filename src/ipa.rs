@@ -0,0 +1,261 @@
+use std::fmt;
+
+use crate::phonemes::{is_vowel, phoneme_name, read_phoneme, Phonemes};
+
+// IPA
+//
+// Conversion between SAM's phonemes and the International Phonetic
+// Alphabet, for American English.
+//
+// Writing IPA, every phoneme that has a name in the phonetic notation gets
+// the symbol below. The parts of stops, diphthongs and breaths that the
+// rules add are left out. Stress 1 to 4 is primary stress, "ˈ", and 5 to 8
+// is secondary stress, "ˌ". SAM keeps the stress on the vowel, so the mark
+// is written right before the vowel, not at the start of the syllable.
+//
+// Reading IPA, each symbol is replaced by the nearest SAM phoneme: there is
+// no "ɒ" in SAM, so it is read as AA. Length marks, tie bars and most
+// diacritics are ignored, and syllable breaks "." are dropped. Primary and
+// secondary stress become SAM's stress 4 and 5 on the next vowel.
+
+/// SAM phonemes and their IPA symbols. The first symbol for a phoneme is
+/// the one written.
+const SYMBOLS: [(&str, &str); 59] = [
+    (" ", " "),
+    (".", "‖"),
+    ("?", "‖"),
+    (",", "|"),
+    ("-", "|"),
+    ("IY", "i"),
+    ("IH", "ɪ"),
+    ("EH", "ɛ"),
+    ("AE", "æ"),
+    ("AA", "ɑ"),
+    ("AH", "ʌ"),
+    ("AO", "ɔ"),
+    ("UH", "ʊ"),
+    ("AX", "ə"),
+    ("IX", "ɨ"),
+    ("ER", "ɝ"),
+    ("UX", "ʉ"),
+    ("OH", "o"),
+    ("RX", "ɹ"),
+    ("LX", "ɫ"),
+    ("WX", "w"),
+    ("YX", "j"),
+    ("WH", "ʍ"),
+    ("R", "ɹ"),
+    ("L", "l"),
+    ("W", "w"),
+    ("Y", "j"),
+    ("M", "m"),
+    ("N", "n"),
+    ("NX", "ŋ"),
+    ("DX", "ɾ"),
+    ("Q", "ʔ"),
+    ("S", "s"),
+    ("SH", "ʃ"),
+    ("F", "f"),
+    ("TH", "θ"),
+    ("/H", "h"),
+    ("/X", "x"),
+    ("Z", "z"),
+    ("ZH", "ʒ"),
+    ("V", "v"),
+    ("DH", "ð"),
+    ("CH", "tʃ"),
+    ("J", "dʒ"),
+    ("EY", "eɪ"),
+    ("AY", "aɪ"),
+    ("OY", "ɔɪ"),
+    ("AW", "aʊ"),
+    ("OW", "oʊ"),
+    ("UW", "u"),
+    ("B", "b"),
+    ("D", "d"),
+    ("G", "ɡ"),
+    ("P", "p"),
+    ("T", "t"),
+    ("K", "k"),
+    ("UL", "l̩"),
+    ("UM", "m̩"),
+    ("UN", "n̩"),
+];
+
+/// More IPA symbols, read as the nearest SAM phoneme.
+const APPROXIMATIONS: [(&str, &str); 25] = [
+    ("ɚ", "ER"),
+    ("ɜ", "ER"),
+    ("ɜɹ", "ER"),
+    ("ɝɹ", "ER"),
+    ("əɹ", "ER"),
+    ("ɒ", "AA"),
+    ("a", "AA"),
+    ("ɐ", "AH"),
+    ("e", "EY"),
+    ("əʊ", "OW"),
+    ("y", "UX"),
+    ("ɯ", "UW"),
+    ("ʏ", "UH"),
+    ("ø", "OH"),
+    ("œ", "EH"),
+    ("r", "R"),
+    ("ɫ", "L"),
+    ("ʁ", "R"),
+    ("ɻ", "R"),
+    ("g", "G"),
+    ("ç", "/H"),
+    ("χ", "/X"),
+    ("ɦ", "/H"),
+    ("ʧ", "CH"),
+    ("ʤ", "J"),
+];
+
+const DIPHTHONGS: [&str; 6] = ["EY", "AY", "OY", "AW", "OW", "UW"];
+
+const PRIMARY_STRESS: char = 'ˈ';
+const SECONDARY_STRESS: char = 'ˌ';
+
+/// Characters read past without effect: length marks, syllable breaks,
+/// diacritics and modifier letters for aspiration and the like.
+fn is_ignored(c: char) -> bool {
+    matches!(c, 'ː' | 'ˑ' | '.' | 'ʰ' | 'ʷ' | 'ʲ' | 'ˠ' | 'ˀ')
+        || ('\u{300}'..='\u{36f}').contains(&c)
+}
+
+/// Write the phonemes in IPA, e.g. "hɛɫˈoʊ" for "/HEHLOW4".
+pub fn phonemes_to_ipa(phonemes: &Phonemes) -> String {
+    let mut output = String::new();
+    let mut previous = String::new();
+    for idx in 0..phonemes.len() {
+        let phoneme = phonemes.phoneme_index[idx];
+        let Some(name) = phoneme_name(phoneme) else {
+            continue;
+        };
+        // The rules add YX or WX after a diphthong, whose symbol already
+        // ends in that sound.
+        let is_ending = name == "YX" || name == "WX";
+        if is_ending && DIPHTHONGS.contains(&previous.as_str()) {
+            continue;
+        }
+        let Some((_, ipa)) = SYMBOLS.iter().find(|(sam, _)| *sam == name) else {
+            continue;
+        };
+
+        if is_vowel(phoneme) {
+            match phonemes.stress[idx] {
+                1..=4 => output.push(PRIMARY_STRESS),
+                5..=8 => output.push(SECONDARY_STRESS),
+                _ => {}
+            }
+        }
+        output.push_str(ipa);
+        previous = name;
+    }
+    output
+}
+
+/// A character in IPA input without a near SAM phoneme.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IpaError {
+    /// Byte offset in the input.
+    pub offset: usize,
+    pub symbol: char,
+}
+
+impl fmt::Display for IpaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no SAM phoneme for \"{}\" at offset {}",
+            self.symbol, self.offset
+        )
+    }
+}
+
+impl std::error::Error for IpaError {}
+
+/// Convert IPA to phonetic input, approximating the sounds that SAM
+/// doesn't have.
+pub fn ipa_to_phonetic(ipa: &str) -> Result<String, IpaError> {
+    let symbols = SYMBOLS
+        .iter()
+        .map(|(sam, ipa)| (*ipa, *sam))
+        // The diphthong endings and LX are only written, never read.
+        .filter(|(_, sam)| !matches!(*sam, "RX" | "LX" | "WX" | "YX" | "?" | "-"))
+        .chain(APPROXIMATIONS)
+        .collect::<Vec<_>>();
+
+    // Tie bars are dropped first, so that "t͡ʃ" is read like "tʃ".
+    let chars = ipa
+        .char_indices()
+        .filter(|(_, c)| !matches!(c, '\u{361}' | '\u{35c}'))
+        .collect::<Vec<_>>();
+
+    let mut output = String::new();
+    let mut stress = None;
+    let mut pos = 0;
+    while pos < chars.len() {
+        let (offset, c) = chars[pos];
+
+        // Longest symbol that matches.
+        let matches = |symbol: &str| {
+            let length = symbol.chars().count();
+            pos + length <= chars.len()
+                && symbol.chars().zip(&chars[pos..]).all(|(a, (_, b))| a == *b)
+        };
+        let found = symbols
+            .iter()
+            .filter(|(symbol, _)| matches(symbol))
+            .max_by_key(|(symbol, _)| symbol.chars().count());
+        if let Some((symbol, sam)) = found {
+            output.push_str(sam);
+            let is_vowel = read_phoneme(sam.as_bytes()).is_some_and(|(p, _)| is_vowel(p));
+            if is_vowel {
+                if let Some(digit) = stress.take() {
+                    output.push(digit);
+                }
+            }
+            pos += symbol.chars().count();
+            continue;
+        }
+
+        match c {
+            PRIMARY_STRESS | '\'' => stress = Some('4'),
+            SECONDARY_STRESS => stress = Some('5'),
+            '\n' | '\t' => output.push(' '),
+            _ if is_ignored(c) => {}
+            _ => return Err(IpaError { offset, symbol: c }),
+        }
+        pos += 1;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ipa::{ipa_to_phonetic, phonemes_to_ipa, IpaError},
+        phonemes::convert_phonemes,
+    };
+
+    #[test]
+    fn sam_to_ipa() {
+        let phonemes = convert_phonemes(b"/HEHLOW4 WER5LD.");
+        assert_eq!(phonemes_to_ipa(&phonemes), "hɛɫˈoʊ wˌɝɫd‖");
+    }
+
+    #[test]
+    fn ipa_to_sam() {
+        assert_eq!(ipa_to_phonetic("həˈloʊ ˌwɝld").unwrap(), "/HAXLOW4 WER5LD");
+        assert_eq!(ipa_to_phonetic("ˈt͡ʃɒkl̩ɪt").unwrap(), "CHAA4KULIHT");
+        assert_eq!(ipa_to_phonetic("ˈbɛː.tɚ‖").unwrap(), "BEH4TER.");
+        assert_eq!(
+            ipa_to_phonetic("kʼ"),
+            Err(IpaError {
+                offset: 1,
+                symbol: 'ʼ'
+            })
+        );
+    }
+}
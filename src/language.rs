@@ -0,0 +1,104 @@
+use std::{fmt, str::FromStr};
+
+/// Languages with built-in letter-to-sound rules, see
+/// `RuleSet::for_language`.
+///
+/// SAM only has the phonemes of American English, so the other languages
+/// are read with the nearest ones: the German "ü" is UX and the "ch" of
+/// "ich" is /H. Words are stressed on the first syllable in Norwegian and
+/// German, and on the syllable that the usual rules or an accent give in
+/// Spanish.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Norwegian,
+    German,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::Norwegian,
+        Language::German,
+        Language::Spanish,
+    ];
+
+    /// Name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "english",
+            Language::Norwegian => "norwegian",
+            Language::German => "german",
+            Language::Spanish => "spanish",
+        }
+    }
+
+    /// ISO 639-1 code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Norwegian => "no",
+            Language::German => "de",
+            Language::Spanish => "es",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownLanguage(pub String);
+
+impl fmt::Display for UnknownLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown language \"{}\", expected one of:", self.0)?;
+        for language in Language::ALL {
+            write!(f, " {}", language)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownLanguage {}
+
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    /// Accepts the names and codes in any case. A region after the code is
+    /// ignored, and Norwegian can also be written with the codes for
+    /// Bokmål and Nynorsk, e.g. "nb-NO".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        let code = normalized.split(['-', '_']).next().unwrap_or_default();
+        let code = match code {
+            "nb" | "nn" => "no",
+            code => code,
+        };
+        Language::ALL
+            .into_iter()
+            .find(|language| language.name() == normalized || language.code() == code)
+            .ok_or_else(|| UnknownLanguage(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::language::Language;
+
+    #[test]
+    fn parse_names() {
+        for language in Language::ALL {
+            assert_eq!(language.name().parse(), Ok(language));
+            assert_eq!(language.code().parse(), Ok(language));
+        }
+        assert_eq!("nb-NO".parse(), Ok(Language::Norwegian));
+        assert_eq!("German".parse(), Ok(Language::German));
+        assert!("klingon".parse::<Language>().is_err());
+    }
+}
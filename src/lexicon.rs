@@ -0,0 +1,186 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{arpabet::arpabet_to_sam, validate::validate_phonetic};
+
+// LEXICON
+//
+// A lexicon gives the pronunciation of whole words in SAM's phonetic
+// notation. The reciter looks every word up in the lexicon before it tries
+// its rules, see `Reciter`. Words are matched without regard to case.
+//
+// A lexicon can be read from a dictionary in the format of CMUdict:
+//
+//     ;;; comment
+//     HELLO  HH AH0 L OW1
+//     HELLO(1)  HH EH0 L OW1
+//
+// Only the first pronunciation of a word is kept, and text after a "#" is
+// a comment.
+//
+// A user lexicon, for names, brands and jargon that the rules get wrong, is
+// a text file with a word and its phonetic input on each line. Values may
+// be quoted, which makes the file valid TOML:
+//
+//     # comment
+//     sam = SAE4M
+//     "Nvidia" = "EHNVIH4DIYAX"
+//
+// A word is letters and apostrophes, like the words the reciter reads.
+
+#[derive(Clone, Debug, Default)]
+pub struct Lexicon {
+    words: HashMap<String, String>,
+}
+
+/// A line of a dictionary that couldn't be read.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LexiconError {
+    /// Line number, from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LexiconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for LexiconError {}
+
+impl Lexicon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a dictionary in CMUdict format.
+    pub fn from_cmudict(text: &str) -> Result<Self, LexiconError> {
+        let mut lexicon = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let error = |message: String| LexiconError {
+                line: idx + 1,
+                message,
+            };
+
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with(";;;") {
+                continue;
+            }
+            let Some((word, arpabet)) = line.split_once(char::is_whitespace) else {
+                return Err(error("no pronunciation".to_string()));
+            };
+            // Alternative pronunciations are numbered, e.g. "HELLO(1)".
+            if word.ends_with(')') && word.contains('(') {
+                continue;
+            }
+            let phonetic = arpabet_to_sam(arpabet).map_err(|e| error(e.to_string()))?;
+            if lexicon.get(word).is_none() {
+                lexicon.insert(word, &phonetic);
+            }
+        }
+        Ok(lexicon)
+    }
+
+    /// Read a user lexicon.
+    pub fn parse(text: &str) -> Result<Self, LexiconError> {
+        let mut lexicon = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let error = |message: String| LexiconError {
+                line: idx + 1,
+                message,
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((word, phonetic)) = line.split_once('=') else {
+                return Err(error("expected word = phonemes".to_string()));
+            };
+            let word = unquote(word.trim()).ok_or_else(|| error("unclosed quote".to_string()))?;
+            // A comment may follow a quoted value.
+            let phonetic = match phonetic.trim() {
+                value if value.starts_with('"') => {
+                    let end = value[1..].find('"').map(|end| end + 2);
+                    end.and_then(|end| unquote(&value[..end]))
+                        .ok_or_else(|| error("unclosed quote".to_string()))?
+                }
+                value => value.split(" #").next().unwrap_or_default().trim(),
+            };
+
+            if word.is_empty() || !word.chars().all(|c| c.is_ascii_alphabetic() || c == '\'') {
+                return Err(error(format!("\"{}\" is not a word", word)));
+            }
+            if let Some(diagnostic) = validate_phonetic(phonetic).first() {
+                return Err(error(format!("{}: {}", phonetic, diagnostic.message)));
+            }
+            lexicon.insert(word, phonetic);
+        }
+        Ok(lexicon)
+    }
+
+    /// Add the words of another lexicon, which take precedence.
+    pub fn extend(&mut self, other: Lexicon) {
+        self.words.extend(other.words);
+    }
+
+    /// Add a word, replacing any pronunciation it had.
+    pub fn insert(&mut self, word: &str, phonetic: &str) {
+        self.words
+            .insert(word.to_ascii_uppercase(), phonetic.to_string());
+    }
+
+    /// Phonetic input for a word.
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.words
+            .get(&word.to_ascii_uppercase())
+            .map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Remove the quotes around a string, if it has them.
+fn unquote(text: &str) -> Option<&str> {
+    match text.strip_prefix('"') {
+        Some(rest) => rest.strip_suffix('"'),
+        None => Some(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexicon::Lexicon;
+
+    #[test]
+    fn reads_cmudict() {
+        let dict = ";;; test\nHELLO  HH AH0 L OW1\nHELLO(1)  HH EH0 L OW1\ntomato T AH0 M EY1 T OW2 # us\n";
+        let lexicon = Lexicon::from_cmudict(dict).unwrap();
+        assert_eq!(lexicon.len(), 2);
+        assert_eq!(lexicon.get("Hello"), Some("/HAXLOW4"));
+        assert_eq!(lexicon.get("TOMATO"), Some("TAXMEY4TOW5"));
+
+        let error = Lexicon::from_cmudict("A  AH0\nB  B IY9\n").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn reads_user_lexicon() {
+        let text = "# names\nsam = SAE4M\n\"Nvidia\" = \"EHNVIH4DIYAX\" # brand\n";
+        let mut lexicon = Lexicon::parse(text).unwrap();
+        assert_eq!(lexicon.get("SAM"), Some("SAE4M"));
+        assert_eq!(lexicon.get("nvidia"), Some("EHNVIH4DIYAX"));
+
+        lexicon.extend(Lexicon::parse("sam = SAA4M").unwrap());
+        assert_eq!(lexicon.get("sam"), Some("SAA4M"));
+
+        assert_eq!(Lexicon::parse("\n\nR2D2 = AA").unwrap_err().line, 3);
+        assert!(Lexicon::parse("sam = sam").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+pub use crate::synthesizer::{Renderer, Synthesizer};
+
+use crate::intonation::IntonationModel;
+
+pub mod arpabet;
+pub mod automation;
+pub mod explain;
+pub mod frames;
+pub mod intonation;
+pub mod ipa;
+pub mod language;
+pub mod lexicon;
+pub mod midi;
+pub mod normalize;
+pub mod phoneme_rules;
+pub mod phonemes;
+pub mod pitch;
+pub mod reciter;
+pub mod remaster;
+pub mod resample;
+pub mod segment;
+pub mod sequence;
+pub mod sing;
+pub mod ssml;
+mod synthesizer;
+mod tables;
+pub mod transliterate;
+pub mod validate;
+pub mod voice;
+pub mod wav;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    pub speed: u8,
+    pub pitch: u8,
+    pub mouth: u8,
+    pub throat: u8,
+    pub singmode: bool,
+    pub intonation: IntonationModel,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            speed: 72,
+            pitch: 64,
+            mouth: 128,
+            throat: 128,
+            singmode: false,
+            intonation: IntonationModel::default(),
+        }
+    }
+}
@@ -1,46 +1,355 @@
-use crate::{
-    phonemes::{convert_phonemes, print_phonemes},
-    frames::FormantTables,
+use std::{fs::File, io::BufWriter, process::ExitCode};
+
+use sam_rs::{
+    explain::explain,
+    intonation::IntonationPreset,
+    ipa::ipa_to_phonetic,
+    language::Language,
+    lexicon::Lexicon,
+    phoneme_rules::{Breath, PhonemeRule, PhonemeRuleSet},
+    pitch::{hz_to_pitch, midi_note_to_pitch},
+    reciter::RuleSet,
+    validate::validate_phonetic,
+    voice::VoicePreset,
+    wav::write_wav,
+    Synthesizer,
 };
 
-mod phonemes;
-mod frames;
-mod tables;
+fn print_usage() {
+    println!("usage: sam [options] Word1 Word2 ....");
+    println!("       sam explain [options] Word1 Word2 ....");
+    println!("           print the rules that pronounce the words");
+    println!("options");
+    println!("    -phonetic            enters phonetic mode. (see below)");
+    println!("    -ssml                input is an SSML document");
+    println!("    -ipa                 input is in IPA, read as the nearest phonemes");
+    println!("    -pitch number        set pitch value (default=64)");
+    println!("    -hz frequency        set pitch as a frequency in Hz");
+    println!("    -note number         set pitch as a MIDI note number (60 = middle C)");
+    println!("    -speed number        set speed value (default=72)");
+    println!("    -throat number       set throat value (default=128)");
+    println!("    -mouth number        set mouth value (default=128)");
+    println!("    --voice name         start from a named voice (see below)");
+    println!("    -intonation name     pitch by stress and punctuation (flat, sam, expressive)");
+    println!("    -wav filename        output to wav");
+    println!("    -sing                special treatment of pitch");
+    println!("    -midi filename       sing the lyrics of a MIDI file, written phonetically");
+    println!(
+        "    -language name       read text in a language (english, norwegian, german, spanish)"
+    );
+    println!("    -cmudict filename    pronounce the words in a CMUdict format dictionary");
+    println!("    -lexicon filename    pronounce the words in a lexicon (word = PHONEMES)");
+    println!("    -rules filename      letter-to-sound rules, tried before the language's rules");
+    println!("    -replace-rules filename  letter-to-sound rules instead of the language's rules");
+    println!("    -disable-rule name   do not apply a phoneme rule (see below)");
+    println!(
+        "    -breath frames       take a breath after this many frames (default=232, 0=never)"
+    );
+    println!("    -audible-breath      breathe out with a soft /H");
+    println!();
+
+    println!("     VOICES               SPEED  PITCH  THROAT  MOUTH");
+    for preset in VoicePreset::ALL {
+        let params = preset.params();
+        println!(
+            "{:20} {:6} {:6} {:7} {:6}",
+            preset.name(),
+            params.speed,
+            params.pitch,
+            params.throat,
+            params.mouth
+        );
+    }
+    println!();
+
+    println!("     PHONEME RULES");
+    for rule in PhonemeRule::ALL {
+        println!("{:24} {}", rule.name(), rule.description());
+    }
+    println!();
 
-pub struct Params {
-    pub speed: u8,
-    pub pitch: u8,
-    pub mouth: u8,
-    pub throat: u8,
-    pub singmode: bool,
+    println!("     VOWELS                            VOICED CONSONANTS    ");
+    println!("IY           f(ee)t                    R        red        ");
+    println!("IH           p(i)n                     L        allow        ");
+    println!("EH           beg                       W        away        ");
+    println!("AE           Sam                       W        whale        ");
+    println!("AA           pot                       Y        you        ");
+    println!("AH           b(u)dget                  M        Sam        ");
+    println!("AO           t(al)k                    N        man        ");
+    println!("OH           cone                      NX       so(ng)        ");
+    println!("UH           book                      B        bad        ");
+    println!("UX           l(oo)t                    D        dog        ");
+    println!("ER           bird                      G        again        ");
+    println!("AX           gall(o)n                  J        judge        ");
+    println!("IX           dig(i)t                   Z        zoo        ");
+    println!("                       ZH       plea(s)ure    ");
+    println!("   DIPHTHONGS                          V        seven        ");
+    println!("EY           m(a)de                    DH       (th)en        ");
+    println!("AY           h(igh)                        ");
+    println!("OY           boy                        ");
+    println!("AW           h(ow)                     UNVOICED CONSONANTS    ");
+    println!("OW           slow                      S         Sam        ");
+    println!("UW           crew                      Sh        fish        ");
+    println!("                                       F         fish        ");
+    println!("                                       TH        thin        ");
+    println!(" SPECIAL PHONEMES                      P         poke        ");
+    println!("UL           sett(le) (=AXL)           T         talk        ");
+    println!("UM           astron(omy) (=AXM)        K         cake        ");
+    println!("UN           functi(on) (=AXN)         CH        speech        ");
+    println!("Q            kitt-en (glottal stop)    /H        a(h)ead    ");
 }
 
-impl Default for Params {
-    fn default() -> Self {
-        Self {
-            speed: 72,
-            pitch: 64,
-            mouth: 128,
-            throat: 128,
-            singmode: false,
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        print_usage();
+        return ExitCode::FAILURE;
+    }
+
+    // Explaining reads the same options, but makes no audio.
+    let explaining = args[0] == "explain";
+    let mut input = String::new();
+    let mut phonetic = false;
+    let mut ssml = false;
+    let mut ipa = false;
+    let mut singmode = false;
+    let mut wav_filename = None;
+    let mut midi_filename = None;
+    let mut cmudict_filename = None;
+    let mut lexicon_filename = None;
+    let mut rules_file = None;
+    let mut phoneme_rules = PhonemeRuleSet::default();
+    let mut breath = Breath::default();
+    let mut language = Language::English;
+    let mut voice = VoicePreset::Sam;
+    let mut intonation = IntonationPreset::Sam;
+    let (mut speed, mut pitch, mut mouth, mut throat) = (None, None, None, None);
+
+    let mut args = args.into_iter().skip(explaining as usize);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            input.push_str(&arg);
+            input.push(' ');
+            continue;
+        }
+
+        let option = arg.trim_start_matches('-');
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        let number = |value: String| {
+            value
+                .parse::<u8>()
+                .map_err(|_| format!("invalid value for -{}: {}", option, value))
+        };
+
+        let result = match option {
+            "wav" => value().map(|v| wav_filename = Some(v)),
+            "midi" => value().map(|v| midi_filename = Some(v)),
+            "cmudict" => value().map(|v| cmudict_filename = Some(v)),
+            "lexicon" => value().map(|v| lexicon_filename = Some(v)),
+            "rules" => value().map(|v| rules_file = Some((v, false))),
+            "replace-rules" => value().map(|v| rules_file = Some((v, true))),
+            "breath" => value().and_then(number).map(|v| breath.after_frames = v),
+            "audible-breath" => {
+                breath.sound = Breath::audible().sound;
+                Ok(())
+            }
+            "sing" => {
+                singmode = true;
+                Ok(())
+            }
+            "phonetic" => {
+                phonetic = true;
+                Ok(())
+            }
+            "ssml" => {
+                ssml = true;
+                Ok(())
+            }
+            "ipa" => {
+                ipa = true;
+                Ok(())
+            }
+            "voice" => value().and_then(|v| {
+                voice = v.parse().map_err(|e| format!("{}", e))?;
+                Ok(())
+            }),
+            "disable-rule" => value().and_then(|v| {
+                let rule = v.parse::<PhonemeRule>().map_err(|e| format!("{}", e))?;
+                phoneme_rules.set_enabled(rule, false);
+                Ok(())
+            }),
+            "intonation" => value().and_then(|v| {
+                intonation = v.parse().map_err(|e| format!("{}", e))?;
+                Ok(())
+            }),
+            "language" => value().and_then(|v| {
+                language = v.parse().map_err(|e| format!("{}", e))?;
+                Ok(())
+            }),
+            "pitch" => value().and_then(number).map(|v| pitch = Some(v)),
+            "hz" => value().and_then(|v| {
+                let hz = v
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid value for -hz: {}", v))?;
+                pitch = Some(hz_to_pitch(hz));
+                Ok(())
+            }),
+            "note" => value()
+                .and_then(number)
+                .map(|v| pitch = Some(midi_note_to_pitch(v))),
+            "speed" => value().and_then(number).map(|v| speed = Some(v)),
+            "mouth" => value().and_then(number).map(|v| mouth = Some(v)),
+            "throat" => value().and_then(number).map(|v| throat = Some(v)),
+            _ => Err(format!("unknown option {}", arg)),
+        };
+
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            print_usage();
+            return ExitCode::FAILURE;
         }
     }
-}
 
-fn main() {
-    let input = "/HAALAOAO MAYN NAAMAEAE IHSTT SAEBAASTTIHAAN";
-    println!("phonetic input: {}", input);
+    // Explicit settings take precedence over the voice, regardless of order.
+    let mut params = voice.params();
+    params.speed = speed.unwrap_or(params.speed);
+    params.pitch = pitch.unwrap_or(params.pitch);
+    params.mouth = mouth.unwrap_or(params.mouth);
+    params.throat = throat.unwrap_or(params.throat);
+    params.singmode = singmode;
+    params.intonation = intonation.model();
 
-    let params = Params::default();
+    let mut synthesizer = Synthesizer::new(params);
+    synthesizer.set_language(language);
+    phoneme_rules.breath = Some(breath).filter(|breath| breath.after_frames != 0);
+    synthesizer.phoneme_rules = phoneme_rules;
+    // The user lexicon is read last, so that its words take precedence.
+    let lexicons: [(_, fn(&str) -> _); 2] = [
+        (cmudict_filename, Lexicon::from_cmudict),
+        (lexicon_filename, Lexicon::parse),
+    ];
+    for (filename, read) in lexicons {
+        let Some(filename) = filename else {
+            continue;
+        };
+        let data = std::fs::read(&filename).map_err(|e| e.to_string());
+        // Older versions of CMUdict are in Latin-1.
+        let lexicon =
+            data.and_then(|data| read(&String::from_utf8_lossy(&data)).map_err(|e| e.to_string()));
+        match lexicon {
+            Ok(lexicon) => synthesizer.reciter.lexicon.extend(lexicon),
+            Err(err) => {
+                eprintln!("could not read {}: {}", filename, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Some((filename, replace)) = rules_file {
+        let rules = std::fs::read_to_string(&filename)
+            .map_err(|e| e.to_string())
+            .and_then(|text| RuleSet::parse(&text).map_err(|e| e.to_string()))
+            .map(|rules| rules.named(&filename));
+        match rules {
+            Ok(rules) if replace => synthesizer.reciter.rules = rules,
+            Ok(rules) => synthesizer.reciter.rules.prepend(rules),
+            Err(err) => {
+                eprintln!("could not read {}: {}", filename, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
 
-    let phonemes = convert_phonemes(input.as_bytes());
-    print_phonemes(&phonemes);
+    if explaining {
+        return match explain(&synthesizer.reciter, &synthesizer.phoneme_rules, &input) {
+            Some(explanation) => {
+                print!("{}", explanation);
+                ExitCode::SUCCESS
+            }
+            None => {
+                eprintln!("too much text to explain at once, try a few words");
+                ExitCode::FAILURE
+            }
+        };
+    }
 
-    let formant_tables = FormantTables::from_params(&params);
-    let frames = frames::mk_frames(&params, &phonemes, &formant_tables);
-    frames::print_frames(&frames);
+    let Some(wav_filename) = wav_filename else {
+        eprintln!("no audio output available, use -wav");
+        return ExitCode::FAILURE;
+    };
 
-    let buffer = frames::mk_wav(&frames);
+    let buffer = match midi_filename {
+        Some(midi_filename) => {
+            let song = std::fs::read(&midi_filename).map_err(|e| e.to_string());
+            match song.and_then(|data| synthesizer.sing_midi(&data).map_err(|e| e.to_string())) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    eprintln!("could not sing {}: {}", midi_filename, err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None if ssml => match synthesizer.synthesize_ssml(&input) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                eprintln!("invalid SSML: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None if ipa => match ipa_to_phonetic(&input).map(|p| synthesizer.synthesize_phonetic(&p)) {
+            Ok(Ok(buffer)) => buffer,
+            Ok(Err(err)) => {
+                eprintln!("could not synthesize: {}", err);
+                return ExitCode::FAILURE;
+            }
+            Err(err) => {
+                eprintln!("invalid IPA: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None if phonetic => {
+            let input = input.to_ascii_uppercase();
+            let diagnostics = validate_phonetic(&input);
+            for diagnostic in &diagnostics {
+                eprintln!(
+                    "column {}: {}",
+                    diagnostic.span.start + 1,
+                    diagnostic.message
+                );
+            }
+            if !diagnostics.is_empty() {
+                return ExitCode::FAILURE;
+            }
+            match synthesizer.synthesize_phonetic(&input) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    eprintln!("column {}: {}", err.span.start + 1, err.message);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => {
+            for unsupported in synthesizer.reciter.unsupported(&input) {
+                let column = input[..unsupported.offset].chars().count() + 1;
+                eprintln!("column {}: {}", column, unsupported);
+            }
+            synthesizer.synthesize_text(&input)
+        }
+    };
+
+    let written = File::create(&wav_filename).and_then(|file| {
+        write_wav(
+            BufWriter::new(file),
+            &buffer,
+            synthesizer.output_sample_rate,
+        )
+    });
+    if let Err(err) = written {
+        eprintln!("could not write {}: {}", wav_filename, err);
+        return ExitCode::FAILURE;
+    }
 
-    println!("{:?}", buffer);
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,306 @@
+use std::fmt;
+
+// STANDARD MIDI FILES
+//
+// Reads just enough of a Standard MIDI File to sing it: note on/off,
+// lyric and tempo events. Format 0 and 1 files are supported, with the
+// events of all tracks merged.
+//
+// SAM has one voice, so the notes are read as a single melody: a note
+// that starts while another is sounding cuts the first one short. The
+// drum channel (channel 10) is ignored. A lyric event is attached to the
+// next note that starts at or after it.
+
+/// A note of the melody, with times in seconds from the start of the file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub start: f64,
+    pub end: f64,
+    /// MIDI key number, 60 is middle C.
+    pub key: u8,
+    pub lyric: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MidiError {
+    /// The data does not start with an `MThd` header.
+    NotMidi,
+    /// The data ends in the middle of a chunk or event.
+    Truncated,
+    /// Format 2 files hold independent sequences, which can't be sung as
+    /// one melody.
+    UnsupportedFormat(u16),
+    /// A lyric that is not phonetic input, or too long to sing as one
+    /// syllable, see `segment::fits`.
+    InvalidLyric(String),
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiError::NotMidi => write!(f, "not a standard MIDI file"),
+            MidiError::Truncated => write!(f, "MIDI file is truncated"),
+            MidiError::UnsupportedFormat(format) => {
+                write!(f, "MIDI file format {} is not supported", format)
+            }
+            MidiError::InvalidLyric(lyric) => {
+                write!(f, "lyric \"{}\" can't be sung as a syllable", lyric)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MidiError {}
+
+const DRUM_CHANNEL: u8 = 9;
+
+/// Tempo until the first tempo event, 120 beats per minute.
+const DEFAULT_TEMPO: u32 = 500_000;
+
+enum Event {
+    /// Microseconds per quarter note.
+    Tempo(u32),
+    Lyric(String),
+    NoteOff(u8),
+    NoteOn(u8),
+}
+
+impl Event {
+    /// Order of events at the same tick: lyrics come before the note they
+    /// belong to, and a note ends before the next one starts.
+    fn order(&self) -> u8 {
+        match self {
+            Event::Tempo(_) | Event::Lyric(_) => 0,
+            Event::NoteOff(_) => 1,
+            Event::NoteOn(_) => 2,
+        }
+    }
+}
+
+/// Read the melody of a Standard MIDI File.
+pub fn read_notes(data: &[u8]) -> Result<Vec<Note>, MidiError> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.bytes(4).ok() != Some(b"MThd".as_slice()) {
+        return Err(MidiError::NotMidi);
+    }
+    let header_length = reader.u32()? as usize;
+    let mut header = Reader {
+        data: reader.bytes(header_length)?,
+        pos: 0,
+    };
+    let format = header.u16()?;
+    let track_count = header.u16()?;
+    let division = header.u16()?;
+    if format > 1 {
+        return Err(MidiError::UnsupportedFormat(format));
+    }
+
+    let mut events = Vec::new();
+    let mut tracks_read = 0;
+    while tracks_read < track_count && reader.pos < data.len() {
+        let id = reader.bytes(4)?;
+        let length = reader.u32()? as usize;
+        let chunk = reader.bytes(length)?;
+        // Unknown chunk types are skipped, as the specification requires.
+        if id == b"MTrk" {
+            read_track(chunk, &mut events)?;
+            tracks_read += 1;
+        }
+    }
+    events.sort_by_key(|(tick, event)| (*tick, event.order()));
+
+    // Ticks are either fractions of a quarter note, whose length is set by
+    // the tempo, or fractions of an SMPTE frame.
+    let seconds_per_tick = |tempo: u32| {
+        if division & 0x8000 != 0 {
+            let frames_per_second = -((division >> 8) as i8) as f64;
+            1.0 / (frames_per_second * (division & 0xff) as f64)
+        } else {
+            tempo as f64 / 1_000_000.0 / division.max(1) as f64
+        }
+    };
+
+    let mut notes = Vec::new();
+    let mut sounding: Option<Note> = None;
+    let mut lyric = None;
+    let mut tempo = DEFAULT_TEMPO;
+    let (mut tick, mut time) = (0, 0.0);
+    for (event_tick, event) in events {
+        time += (event_tick - tick) as f64 * seconds_per_tick(tempo);
+        tick = event_tick;
+
+        match event {
+            Event::Tempo(value) => tempo = value,
+            Event::Lyric(text) => lyric = Some(text),
+            Event::NoteOff(key) => {
+                if sounding.as_ref().is_some_and(|note| note.key == key) {
+                    let note = sounding.take().unwrap();
+                    notes.push(Note { end: time, ..note });
+                }
+            }
+            Event::NoteOn(key) => {
+                if let Some(note) = sounding.take() {
+                    notes.push(Note { end: time, ..note });
+                }
+                sounding = Some(Note {
+                    start: time,
+                    end: time,
+                    key,
+                    lyric: lyric.take(),
+                });
+            }
+        }
+    }
+    notes.extend(sounding.map(|note| Note { end: time, ..note }));
+    notes.retain(|note| note.end > note.start);
+
+    Ok(notes)
+}
+
+fn read_track(data: &[u8], events: &mut Vec<(u64, Event)>) -> Result<(), MidiError> {
+    let mut reader = Reader { data, pos: 0 };
+    let mut tick = 0;
+    let mut running_status = 0;
+    while reader.pos < data.len() {
+        tick += reader.variable_length()? as u64;
+
+        let mut status = reader.u8()?;
+        if status < 0x80 {
+            // Running status, the byte just read is the first data byte.
+            reader.pos -= 1;
+            status = running_status;
+        }
+
+        match status {
+            0xff => {
+                let kind = reader.u8()?;
+                let length = reader.variable_length()? as usize;
+                let data = reader.bytes(length)?;
+                match kind {
+                    0x05 => events.push((
+                        tick,
+                        Event::Lyric(String::from_utf8_lossy(data).into_owned()),
+                    )),
+                    0x51 if length == 3 => events.push((
+                        tick,
+                        Event::Tempo(u32::from_be_bytes([0, data[0], data[1], data[2]])),
+                    )),
+                    0x2f => break,
+                    _ => {}
+                }
+            }
+            0xf0 | 0xf7 => {
+                let length = reader.variable_length()? as usize;
+                reader.bytes(length)?;
+            }
+            0x80..=0xef => {
+                running_status = status;
+                let channel = status & 0x0f;
+                let key = reader.u8()?;
+                // Program change and channel pressure have one data byte.
+                let velocity = match status & 0xf0 {
+                    0xc0 | 0xd0 => 0,
+                    _ => reader.u8()?,
+                };
+                if channel == DRUM_CHANNEL {
+                    continue;
+                }
+                match status & 0xf0 {
+                    0x90 if velocity > 0 => events.push((tick, Event::NoteOn(key))),
+                    0x80 | 0x90 => events.push((tick, Event::NoteOff(key))),
+                    _ => {}
+                }
+            }
+            _ => return Err(MidiError::Truncated),
+        }
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], MidiError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + length)
+            .ok_or(MidiError::Truncated)?;
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, MidiError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, MidiError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, MidiError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn variable_length(&mut self) -> Result<u32, MidiError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(MidiError::Truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::midi::{read_notes, MidiError};
+
+    #[test]
+    fn notes_and_lyrics() {
+        #[rustfmt::skip]
+        let track = [
+            // 60 bpm
+            0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40,
+            0x00, 0xff, 0x05, 0x02, b'L', b'A',
+            0x00, 0x90, 60, 100,
+            // Running status, note off as note on with velocity 0.
+            0x60, 60, 0,
+            // A rest, then a note without lyric cut short by the next note.
+            0x30, 62, 100,
+            0x30, 0xff, 0x05, 0x03, b'M', b'I', b'-',
+            0x00, 0x90, 64, 100,
+            // Drums are ignored.
+            0x00, 0x99, 36, 100,
+            0x81, 0x40, 0x80, 64, 0,
+            0x00, 0xff, 0x2f, 0x00,
+        ];
+        let mut data = b"MThd\0\0\0\x06\0\0\0\x01\0\x60MTrk".to_vec();
+        data.extend((track.len() as u32).to_be_bytes());
+        data.extend(track);
+
+        let notes = read_notes(&data).unwrap();
+        let summary = notes
+            .iter()
+            .map(|note| (note.start, note.end, note.key, note.lyric.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (0.0, 1.0, 60, Some("LA")),
+                (1.5, 2.0, 62, None),
+                (2.0, 4.0, 64, Some("MI-")),
+            ]
+        );
+
+        assert_eq!(read_notes(b"RIFF"), Err(MidiError::NotMidi));
+        assert_eq!(read_notes(&data[..30]), Err(MidiError::Truncated));
+    }
+}
@@ -0,0 +1,595 @@
+use crate::language::Language;
+
+mod english;
+mod german;
+mod norwegian;
+mod spanish;
+
+// TEXT NORMALIZATION
+//
+// The reciter reads digits with its own rules, one digit or a few at a
+// time, and reads most symbols and abbreviations letter by letter. The
+// normalizer runs before it and writes numbers, times, dates, money,
+// percentages, Roman numerals and common abbreviations as words:
+//
+//     Dr. Smith paid $4.99 at 12:30
+//     doctor Smith paid four dollars and ninety-nine cents at twelve thirty
+//
+// It recognizes, at the start of a word:
+//
+//     1,234.5  -7              numbers, with the language's separators
+//     1st  21º  1. Mai         ordinals, with the language's suffixes
+//     1984                     years, four digits from 1100 to 2099
+//     2026-10-18               dates, and 10/18/2026 or 18.10.2026
+//     12:30  9:05 pm           times
+//     $4.99  4,99 €  kr 50     money
+//     50%                      percentages
+//     Henry VIII  World War II Roman numerals after a capitalized word
+//
+// Everything else is copied as it is. The words come from the fields of a
+// `Normalizer`, one for each language, and another language can be added
+// by filling them in.
+
+/// Currencies written with a symbol: $, €, £, ¥ and kr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Currency {
+    Dollar,
+    Euro,
+    Pound,
+    Yen,
+    Krone,
+}
+
+impl Currency {
+    /// The currency whose symbol starts `text`, and the symbol's length.
+    fn read(text: &str) -> Option<(Currency, usize)> {
+        let c = text.chars().next()?;
+        let currency = match c {
+            '$' => Currency::Dollar,
+            '€' => Currency::Euro,
+            '£' => Currency::Pound,
+            '¥' => Currency::Yen,
+            'k' if text.starts_with("kr") && !text[2..].starts_with(char::is_alphanumeric) => {
+                return Some((Currency::Krone, 2));
+            }
+            _ => return None,
+        };
+        Some((currency, c.len_utf8()))
+    }
+}
+
+/// How a language reads numbers, dates and abbreviations.
+#[derive(Clone, Copy, Debug)]
+pub struct Normalizer {
+    /// 123 as "one hundred twenty-three".
+    pub cardinal: fn(u64) -> String,
+    /// 21 as "twenty-first".
+    pub ordinal: fn(u64) -> String,
+    /// 1984 as "nineteen eighty-four".
+    pub year: fn(u64) -> String,
+    /// A day, a month from 1 to 12 and a year.
+    pub date: fn(u32, u32, u64) -> String,
+    /// Hours and minutes.
+    pub time: fn(u32, u32) -> String,
+    /// An amount in whole units and hundredths.
+    pub money: fn(Currency, u64, u64) -> String,
+    pub decimal_separator: char,
+    pub thousands_separator: char,
+    /// Numeric dates are written 18.10.2026 rather than 10/18/2026.
+    pub day_first: bool,
+    /// Word read for the decimal separator, "point".
+    pub decimal_point: &'static str,
+    pub minus: &'static str,
+    pub percent: &'static str,
+    /// Written after a number to make it ordinal, e.g. "st" in "1st".
+    pub ordinal_suffixes: &'static [&'static str],
+    /// Months before which a number with a period is an ordinal, as in
+    /// "1. Mai". A period after a number more often ends a sentence, so
+    /// it is not an ordinal suffix.
+    pub ordinal_months: &'static [&'static str],
+    /// Words in lower case and the ending they give an ordinal after them,
+    /// "n" after "am" in "am ersten Mai".
+    pub ordinal_endings_after: &'static [(&'static str, &'static str)],
+    /// Word written after a time that the time already says, "Uhr" in
+    /// "17:45 Uhr".
+    pub time_word: &'static str,
+    /// Read before a Roman numeral after a name, "the" in "Henry the
+    /// Eighth".
+    pub regnal_article: &'static str,
+    /// Words after which a Roman numeral is a cardinal, in lower case.
+    pub roman_cardinal_after: &'static [&'static str],
+    /// Abbreviations, with their periods, and the words they stand for.
+    pub abbreviations: &'static [(&'static str, &'static str)],
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::for_language(Language::English)
+    }
+}
+
+impl Normalizer {
+    pub fn for_language(language: Language) -> Self {
+        match language {
+            Language::English => english::NORMALIZER,
+            Language::Norwegian => norwegian::NORMALIZER,
+            Language::German => german::NORMALIZER,
+            Language::Spanish => spanish::NORMALIZER,
+        }
+    }
+
+    /// Write the numbers, dates and abbreviations in `text` as words.
+    pub fn normalize(&self, text: &str) -> String {
+        let mut output = String::new();
+        let mut pos = 0;
+        while let Some(c) = text[pos..].chars().next() {
+            let before = &text[..pos];
+            let in_word = before
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+            if !in_word {
+                if let Some((words, length)) = self.read(before, &text[pos..]) {
+                    output.push_str(&words);
+                    pos += length;
+                    // Keep the words apart from a word that follows, as in "4K".
+                    if text[pos..].starts_with(char::is_alphanumeric) {
+                        output.push(' ');
+                    }
+                    continue;
+                }
+            }
+            output.push(c);
+            pos += c.len_utf8();
+        }
+        output
+    }
+
+    /// Words for what starts `text`, and the length read.
+    fn read(&self, before: &str, text: &str) -> Option<(String, usize)> {
+        self.abbreviation(text)
+            .or_else(|| self.roman(before, text))
+            .or_else(|| self.money_after_symbol(text))
+            .or_else(|| self.date(text))
+            .or_else(|| self.time(text))
+            .or_else(|| self.number(before, text))
+    }
+
+    fn abbreviation(&self, text: &str) -> Option<(String, usize)> {
+        let length = text
+            .find(|c: char| !c.is_alphabetic() && c != '.')
+            .unwrap_or(text.len());
+        let word = &text[..length];
+        self.abbreviations
+            .iter()
+            .find(|(abbreviation, _)| *abbreviation == word)
+            .map(|(_, words)| (words.to_string(), length))
+    }
+
+    /// A Roman numeral of I, V, X, L and C after a capitalized word. Larger
+    /// numerals are left alone, since "CD" and "MD" are more often
+    /// abbreviations.
+    fn roman(&self, before: &str, text: &str) -> Option<(String, usize)> {
+        let length = text
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(text.len());
+        let word = &text[..length];
+        if word.len() < 2 && word != "V" {
+            return None;
+        }
+        let value = roman_value(word)?;
+
+        if !before.ends_with(' ') {
+            return None;
+        }
+        let previous = before.split_whitespace().next_back()?;
+        let is_capitalized = previous.chars().next().is_some_and(char::is_uppercase)
+            && previous.chars().all(char::is_alphabetic);
+        if !is_capitalized {
+            return None;
+        }
+        let previous = previous.to_lowercase();
+        let words = if self.roman_cardinal_after.contains(&previous.as_str()) {
+            (self.cardinal)(value)
+        } else if self.regnal_article.is_empty() {
+            (self.ordinal)(value)
+        } else {
+            format!("{} {}", self.regnal_article, (self.ordinal)(value))
+        };
+        Some((words, length))
+    }
+
+    fn money_after_symbol(&self, text: &str) -> Option<(String, usize)> {
+        let (currency, mut length) = Currency::read(text)?;
+        if text[length..].starts_with(' ') {
+            length += 1;
+        }
+        let (digits, decimals, number_length) = self.read_number(&text[length..])?;
+        let words = self.money(currency, digits, decimals)?;
+        Some((words, length + number_length))
+    }
+
+    fn money(&self, currency: Currency, digits: &str, decimals: Option<&str>) -> Option<String> {
+        let units = digits
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse::<u64>()
+            .ok()?;
+        // Fractions of a cent are rounded, as in "$1.999".
+        let cents = match decimals {
+            None => 0,
+            Some(decimals) => {
+                let digit = |idx: usize| {
+                    decimals
+                        .as_bytes()
+                        .get(idx)
+                        .map_or(0, |d| (d - b'0') as u64)
+                };
+                digit(0) * 10 + digit(1) + (digit(2) >= 5) as u64
+            }
+        };
+        Some((self.money)(currency, units + cents / 100, cents % 100))
+    }
+
+    /// 2026-10-18, and 18.10.2026 or 10/18/2026 depending on `day_first`.
+    fn date(&self, text: &str) -> Option<(String, usize)> {
+        let parts = text
+            .split_inclusive(|c: char| !c.is_ascii_digit())
+            .take(3)
+            .collect::<Vec<_>>();
+        let [first, second, third] = parts.as_slice() else {
+            return None;
+        };
+        let separator = first.chars().next_back()?;
+        if !matches!(separator, '-' | '.' | '/') || !second.ends_with(separator) {
+            return None;
+        }
+        let (first, second) = (&first[..first.len() - 1], &second[..second.len() - 1]);
+        let year = digits(third);
+        let length = first.len() + second.len() + year.len() + 2;
+        if text[length..].starts_with(char::is_alphanumeric) {
+            return None;
+        }
+
+        let (year, month, day) = match separator {
+            '-' if first.len() == 4 && second.len() == 2 && year.len() == 2 => {
+                (first, second, year)
+            }
+            '.' | '/' if first.len() <= 2 && second.len() <= 2 && year.len() == 4 => {
+                match (self.day_first, separator) {
+                    (true, _) => (year, second, first),
+                    (false, '/') => (year, first, second),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        let (year, month, day) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        Some(((self.date)(day, month, year), length))
+    }
+
+    /// 12:30, followed by "am" or "pm" if the language has abbreviations
+    /// for them.
+    fn time(&self, text: &str) -> Option<(String, usize)> {
+        let hours = digits(text);
+        let minutes = digits(text[hours.len()..].strip_prefix(':')?);
+        let mut length = hours.len() + 1 + minutes.len();
+        if hours.len() > 2 || minutes.len() != 2 || text[length..].starts_with(':') {
+            return None;
+        }
+        let (hours, minutes) = (hours.parse().ok()?, minutes.parse().ok()?);
+        if hours > 24 || minutes > 59 {
+            return None;
+        }
+
+        let mut words = (self.time)(hours, minutes);
+        let rest = text[length..].strip_prefix(' ').unwrap_or(&text[length..]);
+        let spaces = text.len() - length - rest.len();
+        for (written, abbreviation) in [
+            ("a.m.", "a.m."),
+            ("p.m.", "p.m."),
+            ("am", "a.m."),
+            ("pm", "p.m."),
+        ] {
+            let matches = rest
+                .get(..written.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(written))
+                && !rest[written.len()..].starts_with(char::is_alphanumeric);
+            let expansion = self.abbreviations.iter().find(|(a, _)| *a == abbreviation);
+            if let (true, Some((_, expansion))) = (matches, expansion) {
+                words = format!("{} {}", words, expansion);
+                length += spaces + written.len();
+                break;
+            }
+        }
+        let is_time_word = !self.time_word.is_empty()
+            && rest.starts_with(self.time_word)
+            && !rest[self.time_word.len()..].starts_with(char::is_alphanumeric);
+        if is_time_word {
+            length += spaces + self.time_word.len();
+        }
+        Some((words, length))
+    }
+
+    fn number(&self, before: &str, text: &str) -> Option<(String, usize)> {
+        // A minus sign at the start of a word, not a hyphen as in "10-20".
+        let negative =
+            text.starts_with('-') && before.chars().next_back().is_none_or(char::is_whitespace);
+        let start = negative as usize;
+        let (digits, decimals, number_length) = self.read_number(&text[start..])?;
+        let mut length = start + number_length;
+        let rest = &text[length..];
+
+        let words = if rest.starts_with('%') {
+            length += 1;
+            format!("{} {}", self.number_words(digits, decimals), self.percent)
+        } else if let Some((words, currency_length)) =
+            self.money_after_number(digits, decimals, rest)
+        {
+            length += currency_length;
+            words
+        } else if let Some((words, suffix_length)) = self.ordinal(before, digits, decimals, rest) {
+            length += suffix_length;
+            words
+        } else if number_length == 4 && decimals.is_none() && !negative {
+            match digits.parse() {
+                Ok(year @ 1100..=2099) => (self.year)(year),
+                _ => self.number_words(digits, decimals),
+            }
+        } else {
+            self.number_words(digits, decimals)
+        };
+
+        match negative {
+            true => Some((format!("{} {}", self.minus, words), length)),
+            false => Some((words, length)),
+        }
+    }
+
+    /// 4,99 € or 50 kr.
+    fn money_after_number(
+        &self,
+        digits: &str,
+        decimals: Option<&str>,
+        rest: &str,
+    ) -> Option<(String, usize)> {
+        let symbol = rest.strip_prefix(' ').unwrap_or(rest);
+        let (currency, symbol_length) = Currency::read(symbol)?;
+        let words = self.money(currency, digits, decimals)?;
+        Some((words, rest.len() - symbol.len() + symbol_length))
+    }
+
+    fn ordinal(
+        &self,
+        before: &str,
+        digits: &str,
+        decimals: Option<&str>,
+        rest: &str,
+    ) -> Option<(String, usize)> {
+        if decimals.is_some() {
+            return None;
+        }
+        let before_month = rest.strip_prefix(". ").is_some_and(|rest| {
+            let word = rest
+                .split(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or_default();
+            self.ordinal_months
+                .iter()
+                .any(|month| month.to_lowercase() == word.to_lowercase())
+        });
+        let suffix = self
+            .ordinal_suffixes
+            .iter()
+            .find(|suffix| {
+                rest.get(..suffix.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(suffix))
+                    && !rest[suffix.len()..].starts_with(char::is_alphanumeric)
+            })
+            .or(before_month.then_some(&"."))?;
+        let words = (self.ordinal)(digits.parse().ok()?);
+        // The feminine ordinal indicator, as in the Spanish "1ª", changes
+        // the endings in -o to -a.
+        let words = match suffix.ends_with('ª') {
+            true => words
+                .split(' ')
+                .map(|word| match word.strip_suffix('o') {
+                    Some(stem) => format!("{}a", stem),
+                    None => word.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            false => words,
+        };
+        let previous = before
+            .strip_suffix(' ')
+            .and_then(|before| before.split_whitespace().next_back())
+            .map(str::to_lowercase);
+        let ending = self
+            .ordinal_endings_after
+            .iter()
+            .find(|(word, _)| Some(*word) == previous.as_deref())
+            .map_or("", |(_, ending)| ending);
+        Some((words + ending, suffix.len()))
+    }
+
+    /// A number at the start of `text`: its digits without separators,
+    /// the digits after the decimal separator, and the length read.
+    fn read_number<'a>(&self, text: &'a str) -> Option<(&'a str, Option<&'a str>, usize)> {
+        let first = digits(text);
+        if first.is_empty() {
+            return None;
+        }
+        let mut length = first.len();
+        // Separators only count between groups of three digits, so that
+        // "1,2,3" is a list.
+        let mut grouped = first.len() <= 3;
+        while grouped {
+            let group = text[length..]
+                .strip_prefix(self.thousands_separator)
+                .map(digits)
+                .unwrap_or_default();
+            grouped = group.len() == 3;
+            if grouped {
+                length += self.thousands_separator.len_utf8() + 3;
+            }
+        }
+        let decimals = text[length..]
+            .strip_prefix(self.decimal_separator)
+            .map(digits)
+            .filter(|decimals| !decimals.is_empty());
+        let integer = &text[..length];
+        if let Some(decimals) = decimals {
+            length += self.decimal_separator.len_utf8() + decimals.len();
+        }
+        Some((integer, decimals, length))
+    }
+
+    /// A cardinal, with the decimals read one digit at a time.
+    fn number_words(&self, integer: &str, decimals: Option<&str>) -> String {
+        let digits = integer
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>();
+        let mut words = match digits.parse() {
+            Ok(n) => (self.cardinal)(n),
+            Err(_) => self.digit_words(&digits),
+        };
+        if let Some(decimals) = decimals {
+            words = format!(
+                "{} {} {}",
+                words,
+                self.decimal_point,
+                self.digit_words(decimals)
+            );
+        }
+        words
+    }
+
+    fn digit_words(&self, digits: &str) -> String {
+        digits
+            .bytes()
+            .map(|digit| (self.cardinal)((digit - b'0').into()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The ASCII digits at the start of `text`.
+fn digits(text: &str) -> &str {
+    let length = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    &text[..length]
+}
+
+/// Value of a Roman numeral of I, V, X, L and C, written the usual way.
+fn roman_value(numeral: &str) -> Option<u64> {
+    let values = numeral
+        .chars()
+        .map(|c| match c {
+            'I' => Some(1),
+            'V' => Some(5),
+            'X' => Some(10),
+            'L' => Some(50),
+            'C' => Some(100),
+            _ => None,
+        })
+        .collect::<Option<Vec<u64>>>()?;
+    let mut value = 0;
+    let mut idx = 0;
+    while idx < values.len() {
+        // A smaller numeral before a larger one is taken from it, "IX".
+        match values.get(idx + 1) {
+            Some(next) if *next > values[idx] => {
+                value += next - values[idx];
+                idx += 2;
+            }
+            _ => {
+                value += values[idx];
+                idx += 1;
+            }
+        }
+    }
+    // Only the usual way of writing it, so not "IIII" or "VX".
+    (value > 0 && to_roman(value) == numeral).then_some(value)
+}
+
+fn to_roman(mut value: u64) -> String {
+    let mut numeral = String::new();
+    for (digit_value, digits) in [
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ] {
+        while value >= digit_value {
+            numeral.push_str(digits);
+            value -= digit_value;
+        }
+    }
+    numeral
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{language::Language, normalize::Normalizer};
+
+    #[test]
+    fn english() {
+        let normalizer = Normalizer::default();
+        for (text, words) in [
+            ("Dr. Smith paid $4.99", "doctor Smith paid four dollars and ninety-nine cents"),
+            ("at 12:30 or 9:05 pm.", "at twelve thirty or nine oh five pee em."),
+            ("on 2026-10-18", "on October eighteenth twenty twenty-six"),
+            ("10/18/2026", "October eighteenth twenty twenty-six"),
+            ("1,234,567 and -3.25", "one million two hundred thirty-four thousand five hundred sixty-seven and minus three point two five"),
+            ("in 1984, 50% of 1,2,3", "in nineteen eighty-four, fifty percent of one,two,three"),
+            ("the 21st, 4K", "the twenty-first, four K"),
+            ("Henry VIII and World War II, I", "Henry the eighth and World War two, I"),
+            ("Chapter IX, Louis XIV, Psalm XC", "Chapter nine, Louis the fourteenth, Psalm ninety"),
+            ("mp3 10-20", "mp3 ten-twenty"),
+            ("$1,234.567 or $1.999", "one thousand two hundred thirty-four dollars and fifty-seven cents or two dollars"),
+        ] {
+            assert_eq!(normalizer.normalize(text), words);
+        }
+    }
+
+    #[test]
+    fn other_languages() {
+        let normalize = |language, text| Normalizer::for_language(language).normalize(text);
+        assert_eq!(
+            normalize(Language::Norwegian, "kl. 14:05 den 17.05.1814, 1234 kr"),
+            "klokka fjorten null fem den syttende mai atten hundre og fjorten, tusen to hundre og trettifire kroner"
+        );
+        assert_eq!(
+            normalize(Language::German, "z.B. 21. 4,99 € oder 3,5%"),
+            "zum Beispiel einundzwanzig. vier Euro und neunundneunzig Cent oder drei Komma fünf Prozent"
+        );
+        assert_eq!(
+            normalize(Language::Spanish, "la 1ª vez, $21 y 1.000.000"),
+            "la primera vez, veintiún dólares y un millón"
+        );
+        assert_eq!(
+            normalize(Language::German, "am 1. Mai um 17:45 Uhr, um 8:00 Uhr"),
+            "am ersten Mai um siebzehn Uhr fünfundvierzig, um acht Uhr"
+        );
+        assert_eq!(
+            normalize(Language::German, "Der 3. Mai, vom 7. Juni bis zum 21. Juli"),
+            "Der dritte Mai, vom siebten Juni bis zum einundzwanzigsten Juli"
+        );
+        assert_eq!(
+            normalize(Language::Norwegian, "17. mai kl. 9:00. 3. Da"),
+            "syttende mai klokka ni. tre. Da"
+        );
+    }
+}
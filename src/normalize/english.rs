@@ -0,0 +1,207 @@
+use crate::normalize::{Currency, Normalizer};
+
+pub(super) const NORMALIZER: Normalizer = Normalizer {
+    cardinal,
+    ordinal,
+    year,
+    date,
+    time,
+    money,
+    decimal_separator: '.',
+    thousands_separator: ',',
+    day_first: false,
+    decimal_point: "point",
+    minus: "minus",
+    percent: "percent",
+    ordinal_suffixes: &["st", "nd", "rd", "th"],
+    ordinal_months: &[],
+    ordinal_endings_after: &[],
+    time_word: "",
+    regnal_article: "the",
+    roman_cardinal_after: &[
+        "act", "appendix", "book", "chapter", "class", "episode", "grade", "level", "part",
+        "phase", "psalm", "scene", "section", "stage", "type", "volume", "war",
+    ],
+    abbreviations: &[
+        ("Mr.", "mister"),
+        ("Mrs.", "missus"),
+        ("Ms.", "miz"),
+        ("Dr.", "doctor"),
+        ("Prof.", "professor"),
+        ("St.", "saint"),
+        ("Jr.", "junior"),
+        ("Sr.", "senior"),
+        ("Mt.", "mount"),
+        ("Ave.", "avenue"),
+        ("Dept.", "department"),
+        ("approx.", "approximately"),
+        ("etc.", "et cetera"),
+        ("e.g.", "for example"),
+        ("i.e.", "that is"),
+        ("vs.", "versus"),
+        ("a.m.", "ay em"),
+        ("p.m.", "pee em"),
+    ],
+};
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "quintillion"),
+    (1_000_000_000_000_000, "quadrillion"),
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// "one hundred twenty-three", without "and".
+fn cardinal(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = n;
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            words.push(format!("{} {}", under_1000(rest / scale), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        words.push(under_1000(rest));
+    }
+    words.join(" ")
+}
+
+fn under_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let tens = match rest {
+        0 => String::new(),
+        1..=19 => ONES[rest as usize].to_string(),
+        _ if rest.is_multiple_of(10) => TENS[rest as usize / 10].to_string(),
+        _ => format!("{}-{}", TENS[rest as usize / 10], ONES[rest as usize % 10]),
+    };
+    match (hundreds, tens.is_empty()) {
+        (0, _) => tens,
+        (_, true) => format!("{} hundred", ONES[hundreds as usize]),
+        (_, false) => format!("{} hundred {}", ONES[hundreds as usize], tens),
+    }
+}
+
+/// The cardinal with its last word made ordinal, "twenty-first".
+fn ordinal(n: u64) -> String {
+    let cardinal = cardinal(n);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |idx| idx + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        _ => match last.strip_suffix('y') {
+            Some(stem) => format!("{}ieth", stem),
+            None => format!("{}th", last),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+/// Years are read in pairs of digits, "nineteen eighty-four", except in
+/// the first decade of a century, "two thousand five".
+fn year(n: u64) -> String {
+    let (century, rest) = (n / 100, n % 100);
+    match n {
+        1100..=1999 | 2010..=2099 => match rest {
+            0 => format!("{} hundred", cardinal(century)),
+            1..=9 => format!("{} oh {}", cardinal(century), cardinal(rest)),
+            _ => format!("{} {}", cardinal(century), cardinal(rest)),
+        },
+        _ => cardinal(n),
+    }
+}
+
+/// "October eighteenth twenty twenty-six".
+fn date(day: u32, month: u32, year_number: u64) -> String {
+    format!(
+        "{} {} {}",
+        MONTHS[month as usize - 1],
+        ordinal(day.into()),
+        year(year_number)
+    )
+}
+
+/// "twelve thirty", "nine oh five" or "ten o'clock".
+fn time(hours: u32, minutes: u32) -> String {
+    match minutes {
+        0 => format!("{} o'clock", cardinal(hours.into())),
+        1..=9 => format!("{} oh {}", cardinal(hours.into()), cardinal(minutes.into())),
+        _ => format!("{} {}", cardinal(hours.into()), cardinal(minutes.into())),
+    }
+}
+
+/// "four dollars and ninety-nine cents".
+fn money(currency: Currency, units: u64, cents: u64) -> String {
+    let (unit, units_name, cent, cents_name) = match currency {
+        Currency::Dollar => ("dollar", "dollars", "cent", "cents"),
+        Currency::Euro => ("euro", "euros", "cent", "cents"),
+        Currency::Pound => ("pound", "pounds", "penny", "pence"),
+        Currency::Yen => ("yen", "yen", "sen", "sen"),
+        Currency::Krone => ("krone", "kroner", "ore", "ore"),
+    };
+    let amount = |n: u64, one: &str, many: &str| {
+        format!("{} {}", cardinal(n), if n == 1 { one } else { many })
+    };
+    match (units, cents) {
+        (_, 0) => amount(units, unit, units_name),
+        (0, _) => amount(cents, cent, cents_name),
+        _ => format!(
+            "{} and {}",
+            amount(units, unit, units_name),
+            amount(cents, cent, cents_name)
+        ),
+    }
+}
@@ -0,0 +1,206 @@
+use crate::normalize::{Currency, Normalizer};
+
+pub(super) const NORMALIZER: Normalizer = Normalizer {
+    cardinal,
+    ordinal,
+    year,
+    date,
+    time,
+    money,
+    decimal_separator: ',',
+    thousands_separator: '.',
+    day_first: true,
+    decimal_point: "Komma",
+    minus: "minus",
+    percent: "Prozent",
+    ordinal_suffixes: &[],
+    ordinal_months: &MONTHS,
+    ordinal_endings_after: &[
+        ("am", "n"),
+        ("beim", "n"),
+        ("im", "n"),
+        ("vom", "n"),
+        ("zum", "n"),
+    ],
+    time_word: "Uhr",
+    regnal_article: "der",
+    roman_cardinal_after: &["band", "kapitel", "klasse", "teil", "weltkrieg"],
+    abbreviations: &[
+        ("bzw.", "beziehungsweise"),
+        ("ca.", "zirka"),
+        ("d.h.", "das heißt"),
+        ("Dr.", "Doktor"),
+        ("Fr.", "Frau"),
+        ("Hr.", "Herr"),
+        ("Nr.", "Nummer"),
+        ("Str.", "Straße"),
+        ("u.a.", "unter anderem"),
+        ("usw.", "und so weiter"),
+        ("z.B.", "zum Beispiel"),
+    ],
+};
+
+const ONES: [&str; 20] = [
+    "null",
+    "eins",
+    "zwei",
+    "drei",
+    "vier",
+    "fünf",
+    "sechs",
+    "sieben",
+    "acht",
+    "neun",
+    "zehn",
+    "elf",
+    "zwölf",
+    "dreizehn",
+    "vierzehn",
+    "fünfzehn",
+    "sechzehn",
+    "siebzehn",
+    "achtzehn",
+    "neunzehn",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig",
+];
+
+const SCALES: [(u64, &str, &str); 4] = [
+    (1_000_000_000_000, "Billion", "Billionen"),
+    (1_000_000_000, "Milliarde", "Milliarden"),
+    (1_000_000, "Million", "Millionen"),
+    (1_000, "tausend", "tausend"),
+];
+
+const MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+/// "zweitausend dreihundert vierundfünfzig". Hundreds and thousands are
+/// written apart, which the rules stress better than one long word.
+fn cardinal(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = n;
+    for (scale, one, many) in SCALES {
+        if rest >= scale {
+            let count = rest / scale;
+            words.push(match count {
+                1 if scale == 1_000 => "eintausend".to_string(),
+                1 => format!("eine {}", one),
+                _ if scale == 1_000 => format!("{}{}", under_1000(count, true), many),
+                _ => format!("{} {}", under_1000(count, false), many),
+            });
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        words.push(under_1000(rest, false));
+    }
+    words.join(" ")
+}
+
+/// `compound` is for a number in front of another word, where one is
+/// "ein".
+fn under_100(n: u64, compound: bool) -> String {
+    match n {
+        1 if compound => "ein".to_string(),
+        0..=19 => ONES[n as usize].to_string(),
+        _ if n.is_multiple_of(10) => TENS[n as usize / 10].to_string(),
+        _ => format!("{}und{}", under_100(n % 10, true), TENS[n as usize / 10]),
+    }
+}
+
+fn under_1000(n: u64, compound: bool) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let hundred = match hundreds {
+        0 => return under_100(rest, compound),
+        _ => format!("{}hundert", under_100(hundreds, true)),
+    };
+    match rest {
+        0 => hundred,
+        _ => format!("{} {}", hundred, under_100(rest, compound)),
+    }
+}
+
+/// "einundzwanzigste", "dritte".
+fn ordinal(n: u64) -> String {
+    let cardinal = cardinal(n);
+    let split = cardinal.rfind(' ').map_or(0, |idx| idx + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "eins" => "erste".to_string(),
+        "drei" => "dritte".to_string(),
+        "sieben" => "siebte".to_string(),
+        "acht" => "achte".to_string(),
+        _ if (1..20).contains(&(n % 100)) => format!("{}te", last),
+        _ => format!("{}ste", last.to_lowercase()),
+    };
+    format!("{}{}", head, last)
+}
+
+/// "neunzehnhundert vierundachtzig", and from 2000 like other numbers.
+fn year(n: u64) -> String {
+    match (n, n % 100) {
+        (1100..=1999, 0) => format!("{}hundert", cardinal(n / 100)),
+        (1100..=1999, rest) => format!("{}hundert {}", cardinal(n / 100), cardinal(rest)),
+        _ => cardinal(n),
+    }
+}
+
+/// "achtzehnter Oktober zweitausend sechsundzwanzig".
+fn date(day: u32, month: u32, year_number: u64) -> String {
+    format!(
+        "{}r {} {}",
+        ordinal(day.into()),
+        MONTHS[month as usize - 1],
+        year(year_number)
+    )
+}
+
+/// "zwölf Uhr dreißig".
+fn time(hours: u32, minutes: u32) -> String {
+    match minutes {
+        0 => format!("{} Uhr", cardinal(hours.into())),
+        _ => format!(
+            "{} Uhr {}",
+            cardinal(hours.into()),
+            cardinal(minutes.into())
+        ),
+    }
+}
+
+/// "vier Euro und neunundneunzig Cent".
+fn money(currency: Currency, units: u64, cents: u64) -> String {
+    let (unit, units_name, cent) = match currency {
+        Currency::Dollar => ("Dollar", "Dollar", "Cent"),
+        Currency::Euro => ("Euro", "Euro", "Cent"),
+        Currency::Pound => ("Pfund", "Pfund", "Pence"),
+        Currency::Yen => ("Yen", "Yen", "Sen"),
+        Currency::Krone => ("Krone", "Kronen", "Öre"),
+    };
+    let units_words = match units {
+        1 => format!("ein {}", unit),
+        _ => format!("{} {}", cardinal(units), units_name),
+    };
+    match (units, cents) {
+        (_, 0) => units_words,
+        (0, _) => format!("{} {}", cardinal(cents), cent),
+        _ => format!("{} und {} {}", units_words, cardinal(cents), cent),
+    }
+}
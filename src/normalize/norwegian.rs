@@ -0,0 +1,210 @@
+use crate::normalize::{Currency, Normalizer};
+
+pub(super) const NORMALIZER: Normalizer = Normalizer {
+    cardinal,
+    ordinal,
+    year,
+    date,
+    time,
+    money,
+    decimal_separator: ',',
+    thousands_separator: '.',
+    day_first: true,
+    decimal_point: "komma",
+    minus: "minus",
+    percent: "prosent",
+    ordinal_suffixes: &[],
+    ordinal_months: &MONTHS,
+    ordinal_endings_after: &[],
+    time_word: "",
+    regnal_article: "den",
+    roman_cardinal_after: &["bind", "del", "kapittel", "klasse", "krig", "verdenskrig"],
+    abbreviations: &[
+        ("bl.a.", "blant annet"),
+        ("ca.", "cirka"),
+        ("dvs.", "det vil si"),
+        ("f.eks.", "for eksempel"),
+        ("kl.", "klokka"),
+        ("mht.", "med hensyn til"),
+        ("nr.", "nummer"),
+        ("osv.", "og så videre"),
+        ("pga.", "på grunn av"),
+        ("Dr.", "doktor"),
+    ],
+};
+
+const ONES: [&str; 20] = [
+    "null", "en", "to", "tre", "fire", "fem", "seks", "sju", "åtte", "ni", "ti", "elleve", "tolv",
+    "tretten", "fjorten", "femten", "seksten", "sytten", "atten", "nitten",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "tjue", "tretti", "førti", "femti", "seksti", "sytti", "åtti", "nitti",
+];
+
+const ORDINALS: [&str; 20] = [
+    "nullte",
+    "første",
+    "andre",
+    "tredje",
+    "fjerde",
+    "femte",
+    "sjette",
+    "sjuende",
+    "åttende",
+    "niende",
+    "tiende",
+    "ellevte",
+    "tolvte",
+    "trettende",
+    "fjortende",
+    "femtende",
+    "sekstende",
+    "syttende",
+    "attende",
+    "nittende",
+];
+
+const SCALES: [(u64, &str, &str); 4] = [
+    (1_000_000_000_000, "billion", "billioner"),
+    (1_000_000_000, "milliard", "milliarder"),
+    (1_000_000, "million", "millioner"),
+    (1_000, "tusen", "tusen"),
+];
+
+const MONTHS: [&str; 12] = [
+    "januar",
+    "februar",
+    "mars",
+    "april",
+    "mai",
+    "juni",
+    "juli",
+    "august",
+    "september",
+    "oktober",
+    "november",
+    "desember",
+];
+
+/// "to hundre og tjuetre", with "og" before the last part under a hundred.
+fn cardinal(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = n;
+    for (scale, one, many) in SCALES {
+        if rest >= scale {
+            let count = rest / scale;
+            words.push(match count {
+                1 if scale == 1_000 => one.to_string(),
+                1 => format!("en {}", one),
+                _ => format!("{} {}", under_1000(count), many),
+            });
+            rest %= scale;
+        }
+    }
+    if rest >= 100 || (rest > 0 && words.is_empty()) {
+        words.push(under_1000(rest));
+    } else if rest > 0 {
+        words.push(format!("og {}", under_100(rest)));
+    }
+    words.join(" ")
+}
+
+fn under_100(n: u64) -> String {
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        _ => format!(
+            "{}{}",
+            TENS[n as usize / 10],
+            ONES[n as usize % 10].replace("null", "")
+        ),
+    }
+}
+
+fn under_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let hundred = match hundreds {
+        0 => return under_100(rest),
+        1 => "hundre".to_string(),
+        _ => format!("{} hundre", ONES[hundreds as usize]),
+    };
+    match rest {
+        0 => hundred,
+        _ => format!("{} og {}", hundred, under_100(rest)),
+    }
+}
+
+/// "tjueførste", "hundrede".
+fn ordinal(n: u64) -> String {
+    let rest = n % 100;
+    let small = match rest {
+        0..=19 => ORDINALS[rest as usize].to_string(),
+        _ if rest.is_multiple_of(10) => format!("{}ende", TENS[rest as usize / 10]),
+        _ => format!(
+            "{}{}",
+            TENS[rest as usize / 10],
+            ORDINALS[rest as usize % 10]
+        ),
+    };
+    match (n - rest, rest) {
+        (0, _) => small,
+        (head, 0) => format!("{}de", cardinal(head)),
+        (head, _) => format!("{} og {}", cardinal(head), small),
+    }
+}
+
+/// "nitten hundre og åttifire", and from 2000 like other numbers.
+fn year(n: u64) -> String {
+    match (n, n % 100) {
+        (1100..=1999, 0) => format!("{} hundre", cardinal(n / 100)),
+        (1100..=1999, rest) => format!("{} hundre og {}", cardinal(n / 100), cardinal(rest)),
+        _ => cardinal(n),
+    }
+}
+
+/// "attende oktober to tusen og tjueseks".
+fn date(day: u32, month: u32, year_number: u64) -> String {
+    format!(
+        "{} {} {}",
+        ordinal(day.into()),
+        MONTHS[month as usize - 1],
+        year(year_number)
+    )
+}
+
+/// "tolv tretti" or "ni null fem".
+fn time(hours: u32, minutes: u32) -> String {
+    match minutes {
+        0 => cardinal(hours.into()),
+        1..=9 => format!(
+            "{} null {}",
+            cardinal(hours.into()),
+            cardinal(minutes.into())
+        ),
+        _ => format!("{} {}", cardinal(hours.into()), cardinal(minutes.into())),
+    }
+}
+
+/// "fire kroner og nittini øre".
+fn money(currency: Currency, units: u64, cents: u64) -> String {
+    let (unit, units_name, cent) = match currency {
+        Currency::Dollar => ("dollar", "dollar", "cent"),
+        Currency::Euro => ("euro", "euro", "cent"),
+        Currency::Pound => ("pund", "pund", "pence"),
+        Currency::Yen => ("yen", "yen", "sen"),
+        Currency::Krone => ("krone", "kroner", "øre"),
+    };
+    let units_words = format!(
+        "{} {}",
+        cardinal(units),
+        if units == 1 { unit } else { units_name }
+    );
+    match (units, cents) {
+        (_, 0) => units_words,
+        (0, _) => format!("{} {}", cardinal(cents), cent),
+        _ => format!("{} og {} {}", units_words, cardinal(cents), cent),
+    }
+}
@@ -0,0 +1,272 @@
+use crate::normalize::{Currency, Normalizer};
+
+pub(super) const NORMALIZER: Normalizer = Normalizer {
+    cardinal,
+    ordinal,
+    year: cardinal,
+    date,
+    time,
+    money,
+    decimal_separator: ',',
+    thousands_separator: '.',
+    day_first: true,
+    decimal_point: "coma",
+    minus: "menos",
+    percent: "por ciento",
+    ordinal_suffixes: &["º", ".º", "ª", ".ª"],
+    ordinal_months: &[],
+    ordinal_endings_after: &[],
+    time_word: "",
+    regnal_article: "",
+    roman_cardinal_after: &["capítulo", "guerra", "libro", "parte", "tomo"],
+    abbreviations: &[
+        ("aprox.", "aproximadamente"),
+        ("Dr.", "doctor"),
+        ("Dra.", "doctora"),
+        ("etc.", "etcétera"),
+        ("núm.", "número"),
+        ("p.ej.", "por ejemplo"),
+        ("Sr.", "señor"),
+        ("Sra.", "señora"),
+        ("Srta.", "señorita"),
+        ("Ud.", "usted"),
+        ("Uds.", "ustedes"),
+    ],
+};
+
+const ONES: [&str; 30] = [
+    "cero",
+    "uno",
+    "dos",
+    "tres",
+    "cuatro",
+    "cinco",
+    "seis",
+    "siete",
+    "ocho",
+    "nueve",
+    "diez",
+    "once",
+    "doce",
+    "trece",
+    "catorce",
+    "quince",
+    "dieciséis",
+    "diecisiete",
+    "dieciocho",
+    "diecinueve",
+    "veinte",
+    "veintiuno",
+    "veintidós",
+    "veintitrés",
+    "veinticuatro",
+    "veinticinco",
+    "veintiséis",
+    "veintisiete",
+    "veintiocho",
+    "veintinueve",
+];
+
+const TENS: [&str; 10] = [
+    "",
+    "",
+    "veinte",
+    "treinta",
+    "cuarenta",
+    "cincuenta",
+    "sesenta",
+    "setenta",
+    "ochenta",
+    "noventa",
+];
+
+const HUNDREDS: [&str; 10] = [
+    "",
+    "ciento",
+    "doscientos",
+    "trescientos",
+    "cuatrocientos",
+    "quinientos",
+    "seiscientos",
+    "setecientos",
+    "ochocientos",
+    "novecientos",
+];
+
+const ORDINAL_UNITS: [&str; 10] = [
+    "", "primero", "segundo", "tercero", "cuarto", "quinto", "sexto", "séptimo", "octavo", "noveno",
+];
+
+const ORDINAL_TENS: [&str; 10] = [
+    "",
+    "décimo",
+    "vigésimo",
+    "trigésimo",
+    "cuadragésimo",
+    "quincuagésimo",
+    "sexagésimo",
+    "septuagésimo",
+    "octogésimo",
+    "nonagésimo",
+];
+
+const ORDINAL_HUNDREDS: [&str; 10] = [
+    "",
+    "centésimo",
+    "ducentésimo",
+    "tricentésimo",
+    "cuadringentésimo",
+    "quingentésimo",
+    "sexcentésimo",
+    "septingentésimo",
+    "octingentésimo",
+    "noningentésimo",
+];
+
+const MONTHS: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+/// "dos mil veintiséis". Millions are counted in thousands, so a billion
+/// is "mil millones".
+fn cardinal(n: u64) -> String {
+    let (millions, rest) = (n / 1_000_000, n % 1_000_000);
+    let millions = match millions {
+        0 => String::new(),
+        1 => "un millón".to_string(),
+        _ => format!("{} millones", apocope(&under_million(millions))),
+    };
+    match (millions.is_empty(), rest) {
+        (true, _) => under_million(rest),
+        (false, 0) => millions,
+        (false, _) => format!("{} {}", millions, under_million(rest)),
+    }
+}
+
+fn under_million(n: u64) -> String {
+    let (thousands, rest) = (n / 1000, n % 1000);
+    let thousands = match thousands {
+        0 => String::new(),
+        1 => "mil".to_string(),
+        _ => format!("{} mil", apocope(&under_1000(thousands))),
+    };
+    match (thousands.is_empty(), rest) {
+        (true, _) => under_1000(rest),
+        (false, 0) => thousands,
+        (false, _) => format!("{} {}", thousands, under_1000(rest)),
+    }
+}
+
+fn under_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let tens = match rest {
+        0..=29 => ONES[rest as usize].to_string(),
+        _ if rest.is_multiple_of(10) => TENS[rest as usize / 10].to_string(),
+        _ => format!(
+            "{} y {}",
+            TENS[rest as usize / 10],
+            ONES[rest as usize % 10]
+        ),
+    };
+    match (hundreds, rest) {
+        (0, _) => tens,
+        (1, 0) => "cien".to_string(),
+        (_, 0) => HUNDREDS[hundreds as usize].to_string(),
+        _ => format!("{} {}", HUNDREDS[hundreds as usize], tens),
+    }
+}
+
+/// "Uno" is shortened to "un" in front of "mil" and "millones".
+fn apocope(words: &str) -> String {
+    if let Some(head) = words.strip_suffix("veintiuno") {
+        format!("{}veintiún", head)
+    } else if let Some(head) = words.strip_suffix("uno") {
+        format!("{}un", head)
+    } else {
+        words.to_string()
+    }
+}
+
+/// "vigésimo primero". From a thousand on, the cardinal is used.
+fn ordinal(n: u64) -> String {
+    if !(1..1000).contains(&n) {
+        return cardinal(n);
+    }
+    let (hundreds, tens, units) = (n / 100, n / 10 % 10, n % 10);
+    let mut words = Vec::new();
+    if hundreds > 0 {
+        words.push(ORDINAL_HUNDREDS[hundreds as usize]);
+    }
+    match (tens, units) {
+        (1, 1) => words.push("undécimo"),
+        (1, 2) => words.push("duodécimo"),
+        _ => {
+            if tens > 0 {
+                words.push(ORDINAL_TENS[tens as usize]);
+            }
+            if units > 0 {
+                words.push(ORDINAL_UNITS[units as usize]);
+            }
+        }
+    }
+    words.join(" ")
+}
+
+/// "dieciocho de octubre de dos mil veintiséis", with "primero" for the
+/// first day.
+fn date(day: u32, month: u32, year: u64) -> String {
+    let day = match day {
+        1 => "primero".to_string(),
+        _ => cardinal(day.into()),
+    };
+    format!(
+        "{} de {} de {}",
+        day,
+        MONTHS[month as usize - 1],
+        cardinal(year)
+    )
+}
+
+/// "doce y treinta" or "diez en punto".
+fn time(hours: u32, minutes: u32) -> String {
+    match minutes {
+        0 => format!("{} en punto", cardinal(hours.into())),
+        _ => format!("{} y {}", cardinal(hours.into()), cardinal(minutes.into())),
+    }
+}
+
+/// "cuatro dólares con noventa y nueve centavos".
+fn money(currency: Currency, units: u64, cents: u64) -> String {
+    let (unit, units_name, cent, cents_name) = match currency {
+        Currency::Dollar => ("dólar", "dólares", "centavo", "centavos"),
+        Currency::Euro => ("euro", "euros", "céntimo", "céntimos"),
+        Currency::Pound => ("libra", "libras", "penique", "peniques"),
+        Currency::Yen => ("yen", "yenes", "sen", "sen"),
+        Currency::Krone => ("corona", "coronas", "ore", "ore"),
+    };
+    let amount = |n: u64, one: &str, many: &str| match n {
+        1 if one == "libra" || one == "corona" => format!("una {}", one),
+        1 => format!("un {}", one),
+        _ => format!("{} {}", apocope(&cardinal(n)), many),
+    };
+    match (units, cents) {
+        (_, 0) => amount(units, unit, units_name),
+        (0, _) => amount(cents, cent, cents_name),
+        _ => format!(
+            "{} con {}",
+            amount(units, unit, units_name),
+            amount(cents, cent, cents_name)
+        ),
+    }
+}
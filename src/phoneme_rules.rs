@@ -0,0 +1,307 @@
+use std::{fmt, str::FromStr};
+
+use crate::sequence::Phoneme;
+
+// PHONEME RULES
+//
+// After the phonetic input is read, `convert_phonemes` rewrites phonemes,
+// e.g. "T R -> CH R", and adjusts their lengths, e.g. lengthening vowels
+// before punctuation, with the fixed rules of the C version. Each of them
+// can be turned off in a `PhonemeRuleSet`, where it then changes nothing.
+// The rules that split stops into their parts are needed to render the
+// phonemes, and are always applied.
+//
+// Input that is longer than 232 frames gets a breath: the last space
+// before becomes a glottal stop. The `Breath` of a `PhonemeRuleSet` sets
+// where and how, and can add a sound to the breath, or be `None` to never
+// take one.
+
+/// A rule of `convert_phonemes` that can be turned off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhonemeRule {
+    DiphthongWx,
+    DiphthongYx,
+    Ul,
+    Um,
+    Un,
+    GlottalStop,
+    TR,
+    DR,
+    VowelR,
+    VowelL,
+    GS,
+    KX,
+    GX,
+    SStop,
+    AlveolarUw,
+    Ch,
+    J,
+    FlapT,
+    FlapD,
+    PunctuationLength,
+    LiquidLength,
+    PlosiveLength,
+    VoicedConsonantLength,
+    NasalStopLength,
+    StopStopLength,
+    DiphthongLength,
+}
+
+impl PhonemeRule {
+    pub const ALL: [PhonemeRule; 26] = [
+        PhonemeRule::DiphthongWx,
+        PhonemeRule::DiphthongYx,
+        PhonemeRule::Ul,
+        PhonemeRule::Um,
+        PhonemeRule::Un,
+        PhonemeRule::GlottalStop,
+        PhonemeRule::TR,
+        PhonemeRule::DR,
+        PhonemeRule::VowelR,
+        PhonemeRule::VowelL,
+        PhonemeRule::GS,
+        PhonemeRule::KX,
+        PhonemeRule::GX,
+        PhonemeRule::SStop,
+        PhonemeRule::AlveolarUw,
+        PhonemeRule::Ch,
+        PhonemeRule::J,
+        PhonemeRule::FlapT,
+        PhonemeRule::FlapD,
+        PhonemeRule::PunctuationLength,
+        PhonemeRule::LiquidLength,
+        PhonemeRule::PlosiveLength,
+        PhonemeRule::VoicedConsonantLength,
+        PhonemeRule::NasalStopLength,
+        PhonemeRule::StopStopLength,
+        PhonemeRule::DiphthongLength,
+    ];
+
+    /// Name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhonemeRule::DiphthongWx => "diphthong-wx",
+            PhonemeRule::DiphthongYx => "diphthong-yx",
+            PhonemeRule::Ul => "ul",
+            PhonemeRule::Um => "um",
+            PhonemeRule::Un => "un",
+            PhonemeRule::GlottalStop => "glottal-stop",
+            PhonemeRule::TR => "t-r",
+            PhonemeRule::DR => "d-r",
+            PhonemeRule::VowelR => "vowel-r",
+            PhonemeRule::VowelL => "vowel-l",
+            PhonemeRule::GS => "g-s",
+            PhonemeRule::KX => "kx",
+            PhonemeRule::GX => "gx",
+            PhonemeRule::SStop => "s-stop",
+            PhonemeRule::AlveolarUw => "alveolar-uw",
+            PhonemeRule::Ch => "ch",
+            PhonemeRule::J => "j",
+            PhonemeRule::FlapT => "flap-t",
+            PhonemeRule::FlapD => "flap-d",
+            PhonemeRule::PunctuationLength => "punctuation-length",
+            PhonemeRule::LiquidLength => "liquid-length",
+            PhonemeRule::PlosiveLength => "plosive-length",
+            PhonemeRule::VoicedConsonantLength => "voiced-consonant-length",
+            PhonemeRule::NasalStopLength => "nasal-stop-length",
+            PhonemeRule::StopStopLength => "stop-stop-length",
+            PhonemeRule::DiphthongLength => "diphthong-length",
+        }
+    }
+
+    /// The rule as the comments of the C version write it.
+    pub fn description(&self) -> &'static str {
+        match self {
+            PhonemeRule::DiphthongWx => "<DIPHTHONG ENDING WITH WX> -> <DIPHTHONG> WX",
+            PhonemeRule::DiphthongYx => "<DIPHTHONG NOT ENDING WITH WX> -> <DIPHTHONG> YX",
+            PhonemeRule::Ul => "UL -> AX L",
+            PhonemeRule::Um => "UM -> AX M",
+            PhonemeRule::Un => "UN -> AX N",
+            PhonemeRule::GlottalStop => {
+                "<STRESSED VOWEL> <SILENCE> <STRESSED VOWEL> -> <STRESSED VOWEL> <SILENCE> Q <VOWEL>"
+            }
+            PhonemeRule::TR => "T R -> CH R",
+            PhonemeRule::DR => "D R -> J R",
+            PhonemeRule::VowelR => "<VOWEL> R -> <VOWEL> RX",
+            PhonemeRule::VowelL => "<VOWEL> L -> <VOWEL> LX",
+            PhonemeRule::GS => "G S -> G Z",
+            PhonemeRule::KX => "K <VOWEL NOT ENDING WITH IY> -> KX",
+            PhonemeRule::GX => "G <VOWEL NOT ENDING WITH IY> -> GX",
+            PhonemeRule::SStop => "S <UNVOICED STOP> -> S <VOICED STOP>",
+            PhonemeRule::AlveolarUw => "<ALVEOLAR> UW -> <ALVEOLAR> UX",
+            PhonemeRule::Ch => "CH -> CH CH'",
+            PhonemeRule::J => "J -> J J'",
+            PhonemeRule::FlapT => "<UNSTRESSED VOWEL> T -> <UNSTRESSED VOWEL> DX",
+            PhonemeRule::FlapD => "<UNSTRESSED VOWEL> D -> <UNSTRESSED VOWEL> DX",
+            PhonemeRule::PunctuationLength => "LENGTHEN BEFORE PUNCTUATION",
+            PhonemeRule::LiquidLength => "<VOWEL> RX | LX <CONSONANT>: SHORTEN",
+            PhonemeRule::PlosiveLength => "<VOWEL> <UNVOICED PLOSIVE>: SHORTEN",
+            PhonemeRule::VoicedConsonantLength => "<VOWEL> <VOICED CONSONANT>: LENGTHEN",
+            PhonemeRule::NasalStopLength => "<NASAL> <STOP CONSONANT>: SET LENGTHS",
+            PhonemeRule::StopStopLength => "<VOICED STOP CONSONANT> <STOP CONSONANT>: SHORTEN",
+            PhonemeRule::DiphthongLength => "<VOICED NON-VOWEL> <DIPHTHONG>: SHORTEN",
+        }
+    }
+}
+
+impl fmt::Display for PhonemeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownPhonemeRule(pub String);
+
+impl fmt::Display for UnknownPhonemeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown phoneme rule \"{}\", expected one of:", self.0)?;
+        for rule in PhonemeRule::ALL {
+            write!(f, " {}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownPhonemeRule {}
+
+impl FromStr for PhonemeRule {
+    type Err = UnknownPhonemeRule;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        PhonemeRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == normalized)
+            .ok_or_else(|| UnknownPhonemeRule(s.to_string()))
+    }
+}
+
+/// Which of the rules `convert_phonemes_with` applies. All of them by
+/// default, as in the C version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhonemeRuleSet {
+    enabled: [bool; PhonemeRule::ALL.len()],
+    /// How long input is broken up with breaths, or `None` to never break
+    /// it up.
+    pub breath: Option<Breath>,
+}
+
+impl Default for PhonemeRuleSet {
+    fn default() -> Self {
+        Self {
+            enabled: [true; PhonemeRule::ALL.len()],
+            breath: Some(Breath::default()),
+        }
+    }
+}
+
+impl PhonemeRuleSet {
+    pub fn set_enabled(&mut self, rule: PhonemeRule, enabled: bool) {
+        self.enabled[rule as usize] = enabled;
+    }
+
+    pub fn is_enabled(&self, rule: PhonemeRule) -> bool {
+        self.enabled[rule as usize]
+    }
+}
+
+/// A breath that `convert_phonemes_with` takes in long input. Pinned
+/// pitches of its phonemes are not used. A pause and sound of more than
+/// about 45 frames in all can leave a phoneme of `MAX_LENGTH` no room,
+/// which `synthesize_phonetic` reports and text and SSML synthesis panic
+/// on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breath {
+    /// Frames after which a breath is taken, at the last space before them.
+    pub after_frames: u8,
+    /// What the space becomes.
+    pub pause: Phoneme,
+    /// Heard after the pause, if any.
+    pub sound: Option<Phoneme>,
+}
+
+impl Default for Breath {
+    /// A glottal stop of 4 frames after 232 frames, as in the C version.
+    fn default() -> Self {
+        Self {
+            after_frames: 232,
+            pause: Phoneme::new(31, 4, 0), // 'Q'
+            sound: None,
+        }
+    }
+}
+
+impl Breath {
+    /// The default breath followed by a soft "/H".
+    pub fn audible() -> Self {
+        Self {
+            sound: Some(Phoneme::new(36, 3, 0)), // '/H'
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        phoneme_rules::{Breath, PhonemeRule, PhonemeRuleSet},
+        phonemes::convert_phonemes_with,
+    };
+
+    #[test]
+    fn disabled_rules() {
+        for rule in PhonemeRule::ALL {
+            assert_eq!(rule.name().parse(), Ok(rule));
+        }
+        assert!("T-R".parse::<PhonemeRule>().is_ok());
+        assert!("tr".parse::<PhonemeRule>().is_err());
+
+        let mut rules = PhonemeRuleSet::default();
+        let phonemes = convert_phonemes_with(b" TRAEK ", &rules);
+        assert_eq!(phonemes.phoneme_index[1], 42); // 'CH'
+
+        rules.set_enabled(PhonemeRule::TR, false);
+        assert!(!rules.is_enabled(PhonemeRule::TR));
+        let phonemes = convert_phonemes_with(b" TRAEK ", &rules);
+        assert_eq!(phonemes.phoneme_index[1], 69); // 'T'
+        assert!(phonemes
+            .rules
+            .iter()
+            .all(|(_, rule)| !rule.starts_with("T R")));
+    }
+
+    #[test]
+    fn breaths() {
+        let input = " SAEM".repeat(30) + " ";
+        let breaths = |rules: &PhonemeRuleSet| {
+            let phonemes = convert_phonemes_with(input.as_bytes(), rules);
+            phonemes
+                .rules
+                .iter()
+                .filter(|(_, rule)| *rule == "BREATH")
+                .map(|(idx, _)| phonemes.phoneme_index[*idx])
+                .collect::<Vec<_>>()
+        };
+
+        let mut rules = PhonemeRuleSet::default();
+        assert_eq!(breaths(&rules), [31, 31]); // 'Q'
+        rules.breath = Some(Breath::audible());
+        assert_eq!(breaths(&rules), [31, 36, 31, 36]); // 'Q', '/H'
+        rules.breath = Some(Breath {
+            after_frames: 100,
+            ..Breath::default()
+        });
+        assert!(breaths(&rules).len() > 2);
+        // Between two spaces there are more frames than between breaths.
+        rules.breath = Some(Breath {
+            after_frames: 5,
+            ..Breath::default()
+        });
+        assert_eq!(breaths(&rules).len(), 30);
+        let phonemes = convert_phonemes_with(b"AY4AY4AY4AY4 AY4", &rules);
+        assert_eq!(phonemes.phoneme_index[8..10], [31, 254]); // 'Q' after four 'AY' 'YX'
+        rules.breath = None;
+        assert!(breaths(&rules).is_empty());
+    }
+}
@@ -1,7 +1,23 @@
+use crate::{
+    frames::{SAMPLE_RATE, TICK_SAMPLES},
+    phoneme_rules::{Breath, PhonemeRule, PhonemeRuleSet},
+};
+
 pub struct Phonemes {
     pub stress: [u8; 256],
     pub phoneme_length: [u8; 256],
     pub phoneme_index: [u8; 256],
+    /// Inline controls and the index of the phoneme they apply to. The
+    /// indices move along when the rules insert phonemes.
+    pub controls: Vec<(usize, Control)>,
+    /// Absolute pitches set with `pin_pitch`, by phoneme index.
+    pub pitch_pins: Vec<(usize, u8)>,
+    /// Index of the phoneme of the phonetic input that each phoneme comes
+    /// from. A phoneme the rules insert comes from the phoneme before it.
+    pub input_index: [u8; 256],
+    /// Rules of `convert_phonemes` that inserted a phoneme or changed it or
+    /// its length, by phoneme index, see `explain`.
+    pub rules: Vec<(usize, &'static str)>,
 }
 
 impl Default for Phonemes {
@@ -10,6 +26,122 @@ impl Default for Phonemes {
             stress: [0; 256],
             phoneme_length: [0; 256],
             phoneme_index: [0; 256],
+            controls: Vec::new(),
+            pitch_pins: Vec::new(),
+            input_index: [0; 256],
+            rules: Vec::new(),
+        }
+    }
+}
+
+// PINNING
+//
+// The length and pitch of a phoneme are normally worked out by the rules
+// and the stress. After `convert_phonemes` has run, single phonemes can be
+// pinned to an exact number of frames and an exact pitch, e.g. to hand-tune
+// an important word. A pinned pitch is used as is for all frames of the
+// phoneme, without the stress offset, the F1 contour or any transition.
+
+impl Phonemes {
+    /// Number of phonemes, up to the 255 terminator.
+    pub fn len(&self) -> usize {
+        self.phoneme_index
+            .iter()
+            .position(|phoneme| *phoneme == 255)
+            .unwrap_or(256)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Make the phoneme at `idx` last exactly `frames` frames.
+    pub fn pin_length(&mut self, idx: usize, frames: u8) {
+        assert!(idx < self.len(), "phoneme index out of range");
+        self.phoneme_length[idx] = frames;
+    }
+
+    /// Sing the phoneme at `idx` at exactly `pitch`, in the units of
+    /// `Params::pitch`.
+    pub fn pin_pitch(&mut self, idx: usize, pitch: u8) {
+        assert!(idx < self.len(), "phoneme index out of range");
+        self.pitch_pins.retain(|(i, _)| *i != idx);
+        self.pitch_pins.push((idx, pitch));
+    }
+}
+
+// INLINE CONTROLS
+//
+// Phonetic input may contain controls in braces, which change the voice
+// in the middle of an utterance:
+//
+//     {pitch=50}    Pitch from the next phoneme on, like `Params::pitch`.
+//     {speed=90}    Speed from the next phoneme on, like `Params::speed`.
+//     {pause=300ms} A pause of 300 milliseconds, also "1.5s", up to
+//                   `MAX_PAUSE_MS`.
+//     {len=12}      Length in frames of the previous phoneme, like a
+//                   stress digit it follows the phoneme, e.g. "AA{len=12}",
+//                   up to `MAX_LENGTH`.
+//
+// Lengths are applied by `convert_phonemes`, and the others when the
+// frames are made, see `Automation`.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Pitch(u8),
+    Speed(u8),
+    /// Milliseconds. The phoneme is a pause inserted for the control.
+    Pause(u32),
+    Length(u8),
+}
+
+impl Control {
+    /// Parse the text between the braces, e.g. "pitch=50".
+    pub(crate) fn parse(text: &[u8]) -> Option<Control> {
+        let text = std::str::from_utf8(text).ok()?.to_ascii_lowercase();
+        let (key, value) = text.split_once('=')?;
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "pitch" => value.parse().ok().map(Control::Pitch),
+            "speed" => value.parse().ok().filter(|v| *v > 0).map(Control::Speed),
+            "len" => value.parse().ok().map(Control::Length),
+            "pause" => {
+                let ms = match value.strip_suffix("ms") {
+                    Some(ms) => ms.trim().parse::<f64>().ok()?,
+                    None => value.strip_suffix('s')?.trim().parse::<f64>().ok()? * 1000.0,
+                };
+                (ms >= 0.0).then(|| Control::Pause(ms.round() as u32))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Longest length of a control, in frames, and of a pause. A phoneme this
+/// long still fits a chunk on its own with the phonemes the rules add, see
+/// `segment::fits`. Longer lengths are cut to it, and `validate_phonetic`
+/// reports them.
+pub const MAX_LENGTH: u8 = 200;
+
+/// Longest pause of a control, in milliseconds. A pause lasts at most
+/// `MAX_LENGTH` frames of 255 ticks, about 7.5 seconds. Longer pauses are
+/// cut to it, and `validate_phonetic` reports them.
+pub const MAX_PAUSE_MS: u32 = 7000;
+
+/// Frames that a pause lasts at speed 72. The frames of a pause get their
+/// own speed, so that the pause lasts the same at any speed.
+pub(crate) fn pause_frames(ms: u32) -> u8 {
+    let frame_ms = 72.0 * TICK_SAMPLES / SAMPLE_RATE as f64 * 1000.0;
+    (ms as f64 / frame_ms).round().clamp(1.0, MAX_LENGTH as f64) as u8
+}
+
+/// Set the lengths chosen with controls, overriding the rules.
+fn apply_length_controls(mem: &mut Phonemes) {
+    for (idx, control) in &mem.controls {
+        match *control {
+            Control::Length(length) => mem.phoneme_length[*idx] = length.min(MAX_LENGTH),
+            Control::Pause(ms) => mem.phoneme_length[*idx] = pause_frames(ms),
+            _ => {}
         }
     }
 }
@@ -50,50 +182,80 @@ pub fn print_phonemes(mem: &Phonemes) {
 }
 
 pub fn convert_phonemes(phonetic: &[u8]) -> Phonemes {
+    convert_phonemes_with(phonetic, &PhonemeRuleSet::default())
+}
+
+/// Like `convert_phonemes`, with only the rules that are enabled in `rules`.
+pub fn convert_phonemes_with(phonetic: &[u8], rules: &PhonemeRuleSet) -> Phonemes {
     let mut mem = Phonemes::default();
     parse_1(&mut mem, phonetic);
-    print_phonemes(&mem);
-    parse_2(&mut mem);
+    parse_2(&mut mem, rules);
     copy_stress(&mut mem);
-    set_phoneme_length(&mut mem);
+    set_phoneme_length(&mut mem, rules);
+    apply_length_controls(&mut mem);
     code41240(&mut mem);
     delete_errors(&mut mem);
-    insert_breath(&mut mem);
+    insert_breath(&mut mem, rules.breath.as_ref());
     mem
 }
 
-fn insert_breath(mem: &mut Phonemes) {
-    let mut cum_length = 0;
+fn insert_breath(mem: &mut Phonemes, breath: Option<&Breath>) {
+    let mut cum_length: u8 = 0;
     let mut idx = 0;
-    let mut other_idx = 255;
+    // Space the next breath replaces, none before the first space and
+    // right after a breath.
+    let mut space_idx = None;
     while mem.phoneme_index[idx] != 255 {
         let phoneme = mem.phoneme_index[idx];
-        cum_length += mem.phoneme_length[idx];
+        // Wraps around like the unsigned char of the C version.
+        cum_length = cum_length.wrapping_add(mem.phoneme_length[idx]);
 
-        if cum_length < 232 {
+        let breath = breath
+            .filter(|breath| cum_length >= breath.after_frames)
+            .zip(space_idx);
+        let Some((breath, breath_idx)) = breath else {
             if phoneme != 254 && FLAGS2[phoneme as usize] & 1 != 0 {
+                // Punctuation ends a breath group too.
+                cum_length = 0;
                 insert(mem, idx + 1, 254, 0, 0);
                 idx += 2;
                 continue;
             }
-            if phoneme == 0 {
-                other_idx = idx;
+            // Pauses from controls keep their length.
+            let is_pause =
+                |(i, control): &(usize, Control)| *i == idx && matches!(control, Control::Pause(_));
+            if phoneme == 0 && !mem.controls.iter().any(is_pause) {
+                space_idx = Some(idx);
             }
 
             idx += 1;
             continue;
-        }
+        };
 
-        mem.phoneme_index[other_idx] = 31;
-        mem.phoneme_length[other_idx] = 4;
-        mem.stress[other_idx] = 0;
+        mem.phoneme_index[breath_idx] = breath.pause.phoneme;
+        mem.phoneme_length[breath_idx] = breath.pause.length;
+        mem.stress[breath_idx] = breath.pause.stress;
+        note(mem, breath_idx, "BREATH");
+        let mut marker_idx = breath_idx + 1;
+        if let Some(sound) = breath.sound {
+            insert(mem, marker_idx, sound.phoneme, sound.length, sound.stress);
+            note(mem, marker_idx, "BREATH");
+            marker_idx += 1;
+        }
 
         cum_length = 0;
-        insert(mem, other_idx + 1, 254, 0, 0);
-        idx = other_idx + 2;
+        space_idx = None;
+        insert(mem, marker_idx, 254, 0, 0);
+        idx = marker_idx + 1;
     }
 }
 
+/// The phoneme before `idx`, a space before the first one. The C version
+/// reads whatever is in memory before the table.
+fn phoneme_before(mem: &Phonemes, idx: usize) -> u8 {
+    idx.checked_sub(1).map_or(0, |idx| mem.phoneme_index[idx])
+}
+
 fn delete_errors(mem: &mut Phonemes) {
     let error_idx = mem.phoneme_index.iter().position(|n| *n > 80).unwrap();
     mem.phoneme_index[error_idx] = 255;
@@ -114,6 +276,12 @@ const FLAGS2: [u8; 78] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
+
+/// Vowels, including diphthongs and their YX/WX endings.
+pub(crate) fn is_vowel(phoneme: u8) -> bool {
+    phoneme < 81 && FLAGS[phoneme as usize] & 128 != 0
+}
+
 fn code41240(mem: &mut Phonemes) {
     let mut idx = 0;
     while mem.phoneme_index[idx] != 255 {
@@ -138,6 +306,8 @@ fn code41240(mem: &mut Phonemes) {
                 PHONEME_LENGTH_TABLE[(phoneme + 2) as usize],
                 mem.stress[idx],
             );
+            note(mem, idx + 1, "<STOP> -> <STOP> <STOP'> <STOP''>");
+            note(mem, idx + 2, "<STOP> -> <STOP> <STOP'> <STOP''>");
             idx += 3;
             continue;
         }
@@ -172,15 +342,17 @@ fn code41240(mem: &mut Phonemes) {
             PHONEME_LENGTH_TABLE[(phoneme + 2) as usize],
             mem.stress[idx],
         );
+        note(mem, idx + 1, "<STOP> -> <STOP> <STOP'> <STOP''>");
+        note(mem, idx + 2, "<STOP> -> <STOP> <STOP'> <STOP''>");
         idx += 3;
     }
 }
-const PHONEME_LENGTH_TABLE: [u8; 80] = [
+pub(crate) const PHONEME_LENGTH_TABLE: [u8; 80] = [
     0, 0x12, 0x12, 0x12, 8, 8, 8, 8, 8, 0xB, 6, 0xC, 0xA, 5, 5, 0xB, 0xA, 0xA, 0xA, 9, 8, 7, 9, 7,
     6, 8, 6, 7, 7, 7, 2, 5, 2, 2, 2, 2, 2, 2, 6, 6, 7, 6, 6, 2, 8, 3, 1, 0x1E, 0xD, 0xC, 0xC, 0xC,
     0xE, 9, 6, 1, 2, 5, 1, 1, 6, 1, 2, 6, 1, 2, 8, 2, 2, 4, 2, 2, 6, 1, 4, 6, 1, 4, 0xC7, 0xFF,
 ];
-fn set_phoneme_length(mem: &mut Phonemes) {
+fn set_phoneme_length(mem: &mut Phonemes, rules: &PhonemeRuleSet) {
     // INCLUDES AdjustLengths from sam.c
 
     let phoneme_stressed_length_table = [
@@ -216,7 +388,7 @@ fn set_phoneme_length(mem: &mut Phonemes) {
 
         {
             let mut idx = 0;
-            while mem.phoneme_index[idx] != 255 {
+            'punctuation: while mem.phoneme_index[idx] != 255 {
                 let phoneme = mem.phoneme_index[idx];
 
                 if FLAGS2[phoneme as usize] & 1 == 0 {
@@ -226,26 +398,35 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                 }
 
                 let end_idx = idx;
-                let start_idx = {
-                    let mut start_idx = idx - 1;
-                    while start_idx > 0
-                        && mem.phoneme_index[start_idx] != 255
-                        && FLAGS[mem.phoneme_index[start_idx] as usize] & 128 == 0
-                    {
-                        start_idx -= 1;
+                let mut start_idx = idx;
+                loop {
+                    // Stop lengthening once the beginning is reached, as the
+                    // C version does.
+                    if start_idx <= 1 {
+                        break 'punctuation;
                     }
-                    start_idx
-                };
+                    start_idx -= 1;
+                    let phoneme = mem.phoneme_index[start_idx];
+                    if phoneme == 255 || FLAGS[phoneme as usize] & 128 != 0 {
+                        break;
+                    }
+                }
 
                 for lengthen_idx in start_idx..end_idx {
                     let phoneme = mem.phoneme_index[lengthen_idx];
                     if phoneme != 255
+                        && rules.is_enabled(PhonemeRule::PunctuationLength)
                         // test for fricative/unvoiced or not voiced
                         && (FLAGS2[phoneme as usize] & 32 == 0 || FLAGS[phoneme as usize] & 4 != 0)
                     {
                         let length = mem.phoneme_length[lengthen_idx];
                         let length = (length >> 1) + length + 1;
                         mem.phoneme_length[lengthen_idx] = length;
+                        note(
+                            mem,
+                            lengthen_idx,
+                            PhonemeRule::PunctuationLength.description(),
+                        );
                     }
                 }
                 idx += 1;
@@ -267,13 +448,17 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                     } else {
                         FLAGS[next_phoneme as usize]
                     };
-                    if FLAGS[next_phoneme as usize] & 64 == 0 {
+                    if flags & 64 == 0 {
                         if next_phoneme == 18 || next_phoneme == 19 {
                             // 'RX' or 'LX'
-                            let nextnext_phoneme = mem.phoneme_index[idx + 1];
-                            if FLAGS[nextnext_phoneme as usize] & 64 != 0 {
+                            let nextnext_phoneme = mem.phoneme_index[idx + 2];
+                            if nextnext_phoneme != 255
+                                && FLAGS[nextnext_phoneme as usize] & 64 != 0
+                                && rules.is_enabled(PhonemeRule::LiquidLength)
+                            {
                                 // RULE: <VOWEL> RX | LX <CONSONANT>
                                 mem.phoneme_length[idx] -= 1;
+                                note(mem, idx, PhonemeRule::LiquidLength.description());
                             }
                         }
 
@@ -283,9 +468,11 @@ fn set_phoneme_length(mem: &mut Phonemes) {
 
                     // Got here if not <VOWEL>
                     if flags & 4 == 0 {
-                        if flags & 1 != 0 {
-                            let length = mem.phoneme_length[idx - 1] >> 3;
-                            mem.phoneme_length[idx - 1] = length;
+                        if flags & 1 != 0 && rules.is_enabled(PhonemeRule::PlosiveLength) {
+                            // RULE: <VOWEL> <UNVOICED PLOSIVE>
+                            let length = mem.phoneme_length[idx];
+                            mem.phoneme_length[idx] = length - (length >> 3);
+                            note(mem, idx, PhonemeRule::PlosiveLength.description());
                         }
                         idx += 1;
                         continue;
@@ -293,9 +480,12 @@ fn set_phoneme_length(mem: &mut Phonemes) {
 
                     // RULE: <VOWEL> <VOICED CONSONANT>
                     // <VOWEL> <WH, R*, L*, W*, Y*, M*, N*, NX, DX, Q*, Z*, ZH, V*, DH, J*, B*, D*, G*, GX>
-                    let length = mem.phoneme_length[idx];
-                    let length = (length >> 2) + length + 1;
-                    mem.phoneme_length[idx] = length;
+                    if rules.is_enabled(PhonemeRule::VoicedConsonantLength) {
+                        let length = mem.phoneme_length[idx];
+                        let length = (length >> 2) + length + 1;
+                        mem.phoneme_length[idx] = length;
+                        note(mem, idx, PhonemeRule::VoicedConsonantLength.description());
+                    }
 
                     idx += 1;
                     continue;
@@ -311,11 +501,13 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                     } else {
                         FLAGS[next_phoneme as usize]
                     };
-                    if flags & 2 != 0 {
+                    if flags & 2 != 0 && rules.is_enabled(PhonemeRule::NasalStopLength) {
                         // Stop consonant
 
                         mem.phoneme_length[idx + 1] = 6;
                         mem.phoneme_length[idx] = 5;
+                        note(mem, idx, PhonemeRule::NasalStopLength.description());
+                        note(mem, idx + 1, PhonemeRule::NasalStopLength.description());
                     }
                     idx += 1;
                     continue;
@@ -333,7 +525,10 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                     };
 
                     let next_phoneme = mem.phoneme_index[next_idx];
-                    if next_phoneme == 255 || FLAGS[next_phoneme as usize] & 2 == 0 {
+                    if next_phoneme == 255
+                        || FLAGS[next_phoneme as usize] & 2 == 0
+                        || !rules.is_enabled(PhonemeRule::StopStopLength)
+                    {
                         idx += 1;
                         continue;
                     }
@@ -346,6 +541,10 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                     let length = (length >> 1) + 1;
                     mem.phoneme_length[idx] = length;
 
+                    let rule = PhonemeRule::StopStopLength;
+                    note(mem, idx, rule.description());
+                    note(mem, next_idx, rule.description());
+
                     idx += 1;
                     continue;
                 }
@@ -356,9 +555,12 @@ fn set_phoneme_length(mem: &mut Phonemes) {
                 // liquic consonant?
 
                 if FLAGS2[phoneme as usize] & 16 != 0 {
-                    let prev_phoneme = mem.phoneme_index[idx - 1];
-                    if FLAGS[prev_phoneme as usize] % 2 != 0 {
+                    let prev_phoneme = phoneme_before(mem, idx);
+                    if FLAGS[prev_phoneme as usize] & 2 != 0
+                        && rules.is_enabled(PhonemeRule::DiphthongLength)
+                    {
                         mem.phoneme_length[idx] -= 2;
+                        note(mem, idx, PhonemeRule::DiphthongLength.description());
                     }
                 }
 
@@ -382,14 +584,13 @@ fn copy_stress(mem: &mut Phonemes) {
     // of 5 on the diphtong OY. This routine will copy the stress value of 6 (5+1)
     // to the L that precedes it.
 
-    // TODO(bjornarl): this seems to have bugs.
-
-    let mut idx = 0;
-    while mem.phoneme_index[idx + 1] != 255 {
+    for idx in 0..255 {
         let phoneme = mem.phoneme_index[idx];
+        if phoneme == 255 {
+            break;
+        }
         let next_phoneme = mem.phoneme_index[idx + 1];
         let next_stress = mem.stress[idx + 1];
-        idx += 1;
 
         // This is consonant
         if FLAGS[phoneme as usize] & 64 == 0 {
@@ -397,7 +598,7 @@ fn copy_stress(mem: &mut Phonemes) {
         }
 
         // Next is vowel
-        if FLAGS[next_phoneme as usize] & 128 == 0 {
+        if next_phoneme == 255 || FLAGS[next_phoneme as usize] & 128 == 0 {
             continue;
         }
 
@@ -414,7 +615,7 @@ fn copy_stress(mem: &mut Phonemes) {
     }
 }
 
-fn parse_2(mem: &mut Phonemes) {
+fn parse_2(mem: &mut Phonemes, rules: &PhonemeRuleSet) {
     // Rewrites the phonemes using the following rules:
     //
     //       <DIPHTONG ENDING WITH WX> -> <DIPHTONG ENDING WITH WX> WX
@@ -442,7 +643,6 @@ fn parse_2(mem: &mut Phonemes) {
     let mut idx = 0;
     loop {
         let phoneme = mem.phoneme_index[idx];
-        println!("idx{} phoneme {}", idx, phoneme);
         if phoneme == 0 {
             idx += 1;
             continue;
@@ -462,15 +662,23 @@ fn parse_2(mem: &mut Phonemes) {
             } else {
                 21
             };
-            insert(
-                mem,
-                idx + 1,
-                new_phoneme,
-                mem.phoneme_length[idx],
-                mem.stress[idx],
-            );
+            let rule = if new_phoneme == 20 {
+                PhonemeRule::DiphthongWx
+            } else {
+                PhonemeRule::DiphthongYx
+            };
+            if rules.is_enabled(rule) {
+                insert(
+                    mem,
+                    idx + 1,
+                    new_phoneme,
+                    mem.phoneme_length[idx],
+                    mem.stress[idx],
+                );
+                note(mem, idx + 1, rule.description());
+            }
 
-            last_rules(phoneme, mem, &mut idx);
+            last_rules(phoneme, mem, &mut idx, rules);
             continue; // TODO // Jump to ??? goto pos41749;
         }
 
@@ -478,8 +686,12 @@ fn parse_2(mem: &mut Phonemes) {
         //       UL -> AX L
         // Example: MEDDLE
         if phoneme == 78 {
-            mem.phoneme_index[idx] = 13; // 'AX'
-            insert(mem, idx + 1, 24, mem.phoneme_length[idx], mem.stress[idx]);
+            if rules.is_enabled(PhonemeRule::Ul) {
+                mem.phoneme_index[idx] = 13; // 'AX'
+                insert(mem, idx + 1, 24, mem.phoneme_length[idx], mem.stress[idx]);
+                note(mem, idx, PhonemeRule::Ul.description());
+                note(mem, idx + 1, PhonemeRule::Ul.description());
+            }
             idx += 1;
             continue;
         }
@@ -489,8 +701,12 @@ fn parse_2(mem: &mut Phonemes) {
         // Example: ASTRONOMY
 
         if phoneme == 79 {
-            mem.phoneme_index[idx] = 13; // 'AX'
-            insert(mem, idx + 1, 27, mem.phoneme_length[idx], mem.stress[idx]);
+            if rules.is_enabled(PhonemeRule::Um) {
+                mem.phoneme_index[idx] = 13; // 'AX'
+                insert(mem, idx + 1, 27, mem.phoneme_length[idx], mem.stress[idx]);
+                note(mem, idx, PhonemeRule::Um.description());
+                note(mem, idx + 1, PhonemeRule::Um.description());
+            }
             idx += 1;
             continue;
         }
@@ -499,8 +715,12 @@ fn parse_2(mem: &mut Phonemes) {
         //       UN -> AX N
         // Example: FUNCTION
         if phoneme == 80 {
-            mem.phoneme_index[idx] = 13; // 'AX'
-            insert(mem, idx + 1, 28, mem.phoneme_length[idx], mem.stress[idx]);
+            if rules.is_enabled(PhonemeRule::Un) {
+                mem.phoneme_index[idx] = 13; // 'AX'
+                insert(mem, idx + 1, 28, mem.phoneme_length[idx], mem.stress[idx]);
+                note(mem, idx, PhonemeRule::Un.description());
+                note(mem, idx + 1, PhonemeRule::Un.description());
+            }
             idx += 1;
             continue;
         }
@@ -516,8 +736,10 @@ fn parse_2(mem: &mut Phonemes) {
             let next_phoneme = mem.phoneme_index[idx + 2];
             if (next_phoneme != 255 && FLAGS[next_phoneme as usize] & 128 != 0)
                 && mem.stress[idx + 2] != 0
+                && rules.is_enabled(PhonemeRule::GlottalStop)
             {
                 insert(mem, idx + 2, 31, 0, 0);
+                note(mem, idx + 2, PhonemeRule::GlottalStop.description());
                 idx += 1;
                 continue;
             }
@@ -528,28 +750,36 @@ fn parse_2(mem: &mut Phonemes) {
         // Example: TRACK
         if phoneme == 23 {
             // 'R'
-            let prev_phoneme = mem.phoneme_index[idx - 1];
-            if prev_phoneme == 69 {
+            let prev_phoneme = phoneme_before(mem, idx);
+            if prev_phoneme == 69 && rules.is_enabled(PhonemeRule::TR) {
                 // 'T'
                 mem.phoneme_index[idx - 1] = 42; // 'CH'
                 insert(mem, idx, 43, 0, mem.stress[idx - 1]);
+                note(mem, idx - 1, PhonemeRule::TR.description());
+                note(mem, idx, PhonemeRule::TR.description());
                 idx += 1;
                 continue;
             }
 
-            if prev_phoneme == 57 {
+            if prev_phoneme == 57 && rules.is_enabled(PhonemeRule::DR) {
                 //'D'
                 mem.phoneme_index[idx - 1] = 44; //'J'
                 insert(mem, idx, 45, 0, mem.stress[idx - 1]);
+                note(mem, idx - 1, PhonemeRule::DR.description());
+                note(mem, idx, PhonemeRule::DR.description());
             }
 
             // RULES FOR PHONEMES BEFORE R
             //        <VOWEL> R -> <VOWEL> RX
             // Example: ART
 
-            if phoneme != 0 && FLAGS[phoneme as usize] & 128 != 0 {
+            if phoneme != 0
+                && FLAGS[phoneme as usize] & 128 != 0
+                && rules.is_enabled(PhonemeRule::VowelR)
+            {
                 // vowel
                 mem.phoneme_index[idx] = 18; // 'RX'
+                note(mem, idx, PhonemeRule::VowelR.description());
             }
 
             idx += 1;
@@ -562,8 +792,11 @@ fn parse_2(mem: &mut Phonemes) {
 
         if phoneme == 24 {
             //'L'
-            if FLAGS[mem.phoneme_index[idx - 1] as usize] & 128 != 0 {
+            if FLAGS[phoneme_before(mem, idx) as usize] & 128 != 0
+                && rules.is_enabled(PhonemeRule::VowelL)
+            {
                 mem.phoneme_index[idx] = 19; //'LX'
+                note(mem, idx, PhonemeRule::VowelL.description());
             }
 
             idx += 1;
@@ -579,9 +812,10 @@ fn parse_2(mem: &mut Phonemes) {
 
         if phoneme == 32 {
             // 'S'
-            if mem.phoneme_index[idx - 1] == 60 {
+            if phoneme_before(mem, idx) == 60 && rules.is_enabled(PhonemeRule::GS) {
                 // 'G'
                 mem.phoneme_index[idx] = 38; // 'Z'
+                note(mem, idx, PhonemeRule::GS.description());
             }
 
             idx += 1;
@@ -594,8 +828,11 @@ fn parse_2(mem: &mut Phonemes) {
         if phoneme == 72 {
             // 'K'
             let next_phoneme = mem.phoneme_index[idx + 1];
-            if next_phoneme == 255 || FLAGS[next_phoneme as usize] & 32 == 0 {
+            if (next_phoneme == 255 || FLAGS[next_phoneme as usize] & 32 == 0)
+                && rules.is_enabled(PhonemeRule::KX)
+            {
                 mem.phoneme_index[idx] = 75;
+                note(mem, idx, PhonemeRule::KX.description());
             }
         } else if phoneme == 60 {
             // 'G'
@@ -604,17 +841,22 @@ fn parse_2(mem: &mut Phonemes) {
             // Example: GO
 
             let next_phoneme = mem.phoneme_index[idx + 1];
-            if next_phoneme != 255 && FLAGS[next_phoneme as usize] & 32 == 0 {
+            if next_phoneme != 255
+                && FLAGS[next_phoneme as usize] & 32 == 0
+                && rules.is_enabled(PhonemeRule::GX)
+            {
                 mem.phoneme_index[idx] = 63; // 'GX'
+                note(mem, idx, PhonemeRule::GX.description());
             }
 
             idx += 1;
             continue;
         }
 
-        let can_soften = FLAGS[phoneme as usize] & 1 != 0;
-        if can_soften && mem.phoneme_index[idx - 1] == 32 {
+        let can_soften = FLAGS[phoneme as usize] & 1 != 0 && rules.is_enabled(PhonemeRule::SStop);
+        if can_soften && phoneme_before(mem, idx) == 32 {
             mem.phoneme_index[idx] = phoneme - 12;
+            note(mem, idx, PhonemeRule::SStop.description());
             idx += 1;
             continue;
         }
@@ -623,30 +865,39 @@ fn parse_2(mem: &mut Phonemes) {
         //  sam.c:936:             goto pos41812;
         // ... is unnecessary, it seems to be an optimization.
 
-        last_rules(phoneme, mem, &mut idx);
+        last_rules(phoneme, mem, &mut idx, rules);
     }
 }
 
-fn last_rules(phoneme: u8, mem: &mut Phonemes, idx: &mut usize) {
+fn last_rules(phoneme: u8, mem: &mut Phonemes, idx: &mut usize, rules: &PhonemeRuleSet) {
     if phoneme == 53 {
         // 'UW'
-        if FLAGS2[mem.phoneme_index[*idx - 1] as usize] & 4 != 0 {
+        if FLAGS2[phoneme_before(mem, *idx) as usize] & 4 != 0
+            && rules.is_enabled(PhonemeRule::AlveolarUw)
+        {
             mem.phoneme_index[*idx] = 16;
+            note(mem, *idx, PhonemeRule::AlveolarUw.description());
         }
         *idx += 1;
         return;
     }
     if phoneme == 42 {
-        insert(mem, *idx + 1, 43, 0, mem.stress[*idx]);
+        if rules.is_enabled(PhonemeRule::Ch) {
+            insert(mem, *idx + 1, 43, 0, mem.stress[*idx]);
+            note(mem, *idx + 1, PhonemeRule::Ch.description());
+        }
         *idx += 1;
         return;
     }
     if phoneme == 44 {
-        insert(mem, *idx + 1, 45, 0, mem.stress[*idx]);
+        if rules.is_enabled(PhonemeRule::J) {
+            insert(mem, *idx + 1, 45, 0, mem.stress[*idx]);
+            note(mem, *idx + 1, PhonemeRule::J.description());
+        }
         *idx += 1;
         return;
     }
-    if (phoneme == 69 || phoneme == 57) && FLAGS[mem.phoneme_index[*idx - 1] as usize] & 128 != 0 {
+    if (phoneme == 69 || phoneme == 57) && FLAGS[phoneme_before(mem, *idx) as usize] & 128 != 0 {
         // T or D after vowel.
         let next_phoneme = mem.phoneme_index[*idx + 1];
 
@@ -654,22 +905,45 @@ fn last_rules(phoneme: u8, mem: &mut Phonemes, idx: &mut usize) {
             || (next_phoneme != 0 && FLAGS[next_phoneme as usize] & 128 != 0)
             || (FLAGS[next_phoneme as usize] & 128 != 0 && mem.stress[*idx + 1] != 0)
         {
-            mem.phoneme_index[*idx] = 30; // 'DX'
+            let rule = if phoneme == 69 {
+                PhonemeRule::FlapT
+            } else {
+                PhonemeRule::FlapD
+            };
+            if rules.is_enabled(rule) {
+                mem.phoneme_index[*idx] = 30; // 'DX'
+                note(mem, *idx, rule.description());
+            }
         }
     }
     *idx += 1;
 }
 
+/// Record that a rule inserted or changed the phoneme at `idx`.
+fn note(mem: &mut Phonemes, idx: usize, rule: &'static str) {
+    mem.rules.push((idx, rule));
+}
+
 fn insert(mem: &mut Phonemes, position: usize, ph_idx: u8, ph_len: u8, stress: u8) {
     for i in (position..=253).rev() {
         mem.phoneme_index[i + 1] = mem.phoneme_index[i];
         mem.phoneme_length[i + 1] = mem.phoneme_length[i];
         mem.stress[i + 1] = mem.stress[i];
+        mem.input_index[i + 1] = mem.input_index[i];
     }
 
     mem.phoneme_index[position] = ph_idx;
     mem.phoneme_length[position] = ph_len;
     mem.stress[position] = stress;
+    mem.input_index[position] = mem.input_index[position.saturating_sub(1)];
+
+    let indices = mem.controls.iter_mut().map(|(idx, _)| idx);
+    let indices = indices.chain(mem.pitch_pins.iter_mut().map(|(idx, _)| idx));
+    for idx in indices.chain(mem.rules.iter_mut().map(|(idx, _)| idx)) {
+        if *idx >= position {
+            *idx += 1;
+        }
+    }
 }
 
 const SIGN_INPUT_TABLE_1: [u8; 81] = [
@@ -689,18 +963,71 @@ const SIGN_INPUT_TABLE_2: [u8; 81] = [
     b'N',
 ];
 
+/// Name of a phoneme in the phonetic notation, or `None` for the phonemes
+/// that only the rules add.
+pub(crate) fn phoneme_name(phoneme: u8) -> Option<String> {
+    let sign1 = *SIGN_INPUT_TABLE_1.get(phoneme as usize)?;
+    let sign2 = SIGN_INPUT_TABLE_2[phoneme as usize];
+    match (sign1, sign2) {
+        (b'*', _) => None,
+        (_, b'*') => Some((sign1 as char).to_string()),
+        _ => Some(format!("{}{}", sign1 as char, sign2 as char)),
+    }
+}
+
+/// The phoneme at the start of phonetic input and the number of bytes its
+/// name takes, preferring two-letter names like `parse_1`.
+pub(crate) fn read_phoneme(phonetic: &[u8]) -> Option<(u8, usize)> {
+    let names = || SIGN_INPUT_TABLE_1.iter().zip(SIGN_INPUT_TABLE_2.iter());
+    if let [sign1, sign2, ..] = phonetic {
+        if let Some(idx) = names().position(|(t1, t2)| t1 == sign1 && t2 == sign2 && *t2 != b'*') {
+            return Some((idx as u8, 2));
+        }
+    }
+    let sign1 = phonetic.first()?;
+    names()
+        .position(|(t1, t2)| t1 == sign1 && *t2 == b'*')
+        .map(|idx| (idx as u8, 1))
+}
+
 fn parse_1(mem: &mut Phonemes, phonetic: &[u8]) {
     mem.phoneme_index[255] = 32;
 
     let stress_input_table = [b'*', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8'];
-    let mut output_idx = 0;
+    let mut output_idx: usize = 0;
     let mut input_idx = 0;
     'phoneme_loop: while input_idx < phonetic.len() {
         let sign1 = phonetic[input_idx];
         input_idx += 1;
 
+        // READ CONTROL
+        // Controls that can't be read are skipped, an unclosed one up to the
+        // end of the input, see `validate_phonetic`.
+        if sign1 == b'{' {
+            let Some(length) = phonetic[input_idx..].iter().position(|c| *c == b'}') else {
+                break;
+            };
+            let control = Control::parse(&phonetic[input_idx..input_idx + length]);
+            input_idx += length + 1;
+
+            match control {
+                Some(control @ Control::Length(_)) => {
+                    mem.controls.push((output_idx.saturating_sub(1), control))
+                }
+                Some(control @ Control::Pause(_)) => {
+                    // Silence
+                    mem.phoneme_index[output_idx] = 0;
+                    mem.controls.push((output_idx, control));
+                    output_idx += 1;
+                }
+                Some(control) => mem.controls.push((output_idx, control)),
+                None => {}
+            }
+            continue;
+        }
+
         // READ 2 (NON-WILDCARD IN TABLE2)
-        if input_idx + 1 < phonetic.len() {
+        if input_idx < phonetic.len() {
             let sign2 = phonetic[input_idx];
 
             for (table_idx, (t1, t2)) in SIGN_INPUT_TABLE_1
@@ -743,5 +1070,216 @@ fn parse_1(mem: &mut Phonemes, phonetic: &[u8]) {
         panic!("Parse failed");
     }
 
-    mem.phoneme_index[output_idx + 1] = 255;
+    mem.phoneme_index[output_idx] = 255;
+    for idx in 0..output_idx {
+        mem.input_index[idx] = idx as u8;
+    }
+}
+
+impl Phonemes {
+    /// Write the phonemes in the phonetic notation read by `parse_1`, with
+    /// stress digits and inline controls, e.g. "/HEH3LOW {pause=300ms}".
+    ///
+    /// Phonemes without a name of their own, the parts of stops added by
+    /// the rules and the breath marks, are left out, and pinned pitches
+    /// are not written. So is what the conversion adds again: the glides
+    /// after diphthongs, the stress that `copy_stress` copies and the
+    /// breaths, which are written as the spaces they replaced. What is
+    /// written converts back to the same phonemes, e.g. " AW " to " AW "
+    /// rather than " AWWX ".
+    pub fn to_phonetic_string(&self) -> String {
+        let glides = [
+            PhonemeRule::DiphthongWx.description(),
+            PhonemeRule::DiphthongYx.description(),
+        ];
+        let noted = |idx: usize, rules: &[&str]| {
+            self.rules
+                .iter()
+                .any(|(i, rule)| *i == idx && rules.contains(rule))
+        };
+        let mut text = String::new();
+        for idx in 0..self.len() {
+            let phoneme = self.phoneme_index[idx];
+            // The conversion adds the glide after a diphthong again, but
+            // not after one that a later rule changed, e.g. UW to UX, and
+            // the sound after a breath.
+            let glide =
+                noted(idx, &glides) && FLAGS[self.phoneme_index[idx - 1] as usize] & 16 != 0;
+            let breath = noted(idx, &["BREATH"]);
+            if glide || (breath && idx > 0 && noted(idx - 1, &["BREATH"])) {
+                continue;
+            }
+            let controls = self.controls.iter().filter(|(i, _)| *i == idx);
+            for (_, control) in controls.clone() {
+                match control {
+                    Control::Pitch(pitch) => text += &format!("{{pitch={}}}", pitch),
+                    Control::Speed(speed) => text += &format!("{{speed={}}}", speed),
+                    _ => {}
+                }
+            }
+
+            if let Some((_, Control::Pause(ms))) = controls
+                .clone()
+                .find(|(_, control)| matches!(control, Control::Pause(_)))
+            {
+                text += &format!("{{pause={}ms}}", ms);
+            } else if breath {
+                text.push(' ');
+            } else {
+                match phoneme_name(phoneme) {
+                    Some(name) => text += &name,
+                    None => continue,
+                }
+                if (1..=8).contains(&self.stress[idx]) && !self.copied_stress(idx) {
+                    text.push((b'0' + self.stress[idx]) as char);
+                }
+            }
+
+            for (_, control) in controls {
+                if let Control::Length(length) = control {
+                    text += &format!("{{len={}}}", length);
+                }
+            }
+        }
+        text
+    }
+
+    /// Whether `copy_stress` sets the stress of the phoneme at `idx`, from
+    /// the next phoneme that has a name.
+    fn copied_stress(&self, idx: usize) -> bool {
+        let next = (idx + 1..self.len()).find(|i| phoneme_name(self.phoneme_index[*i]).is_some());
+        let Some(next) = next else {
+            return false;
+        };
+        let next_phoneme = self.phoneme_index[next];
+        let next_stress = self.stress[next];
+        FLAGS[self.phoneme_index[idx] as usize] & 64 != 0
+            && FLAGS[next_phoneme as usize] & 128 != 0
+            && next_stress != 0
+            && next_stress & 128 == 0
+            && self.stress[idx] == next_stress + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        phoneme_rules::{PhonemeRule, PhonemeRuleSet},
+        phonemes::{
+            convert_phonemes, convert_phonemes_with, parse_1, pause_frames, Control, Phonemes,
+        },
+        reciter::text_to_phonemes,
+    };
+
+    #[test]
+    fn inline_controls() {
+        let phonemes = convert_phonemes(b"AA{len=20}{Pitch=50} {pause=0.3s}{speed=90}IY ");
+        let controls = phonemes
+            .controls
+            .iter()
+            .map(|(idx, control)| (phonemes.phoneme_index[*idx], *control))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            controls,
+            [
+                (9, Control::Length(20)),
+                (0, Control::Pitch(50)),
+                (0, Control::Pause(300)),
+                (5, Control::Speed(90)),
+            ]
+        );
+        assert_eq!(phonemes.phoneme_length[phonemes.controls[0].0], 20);
+        assert_eq!(
+            phonemes.phoneme_length[phonemes.controls[2].0],
+            pause_frames(300)
+        );
+
+        // Controls that can't be read are skipped.
+        for input in ["AA{pitch=}IY ", "AA{tone=3}IY ", "AA{pitch=50 IY "] {
+            let phonemes = convert_phonemes(input.as_bytes());
+            assert!(phonemes.controls.is_empty());
+            assert_eq!(phonemes.phoneme_index[0], 9); // 'AA'
+        }
+    }
+
+    #[test]
+    fn pinned_length_and_pitch() {
+        let mut phonemes = convert_phonemes(b"MAA4 ");
+        assert_eq!(phonemes.phoneme_index[..2], [27, 9]);
+        phonemes.pin_length(1, 20);
+        phonemes.pin_pitch(1, 90);
+        phonemes.pin_pitch(1, 40);
+        assert_eq!(phonemes.pitch_pins, [(1, 40)]);
+        assert_eq!(phonemes.phoneme_length[1], 20);
+    }
+
+    #[test]
+    fn end_of_input() {
+        // A two-letter name may end the input, where the C version reads
+        // its terminator as the second sign, and the 255 follows the last
+        // phoneme.
+        let mut phonemes = Phonemes::default();
+        parse_1(&mut phonemes, b" SAEM");
+        assert_eq!(phonemes.phoneme_index[..5], [0, 32, 8, 27, 255]);
+
+        let mut phonemes = Phonemes::default();
+        parse_1(&mut phonemes, b"SAE");
+        assert_eq!(phonemes.phoneme_index[..3], [32, 8, 255]);
+    }
+
+    #[test]
+    fn phonetic_string_round_trip() {
+        for input in [
+            "/HEH3LOW WER4LD.",
+            "AA{len=20}{pitch=50} {pause=300ms}{speed=90}IY5 ",
+            "SHIY4Z DHAX UL UM UN KX?",
+        ] {
+            let mut phonemes = Phonemes::default();
+            parse_1(&mut phonemes, input.as_bytes());
+            assert_eq!(phonemes.to_phonetic_string(), input);
+        }
+
+        // Converted phonemes convert back to the same phonemes, without the
+        // glides, copied stress and breaths that the conversion adds.
+        let texts = [
+            "Nothing is impossible.",
+            "The quick brown fox jumps over the lazy dog.",
+        ]
+        .map(|text| format!("{} ", text_to_phonemes(text)));
+        for input in [" AW ", " LOY5TER ", " KAY5T ", " DHAX ZUW5? "]
+            .into_iter()
+            .chain(texts.iter().map(String::as_str))
+        {
+            let phonemes = convert_phonemes(input.as_bytes());
+            let written = phonemes.to_phonetic_string();
+            let converted = convert_phonemes(written.as_bytes());
+            let len = phonemes.len() + 1;
+            assert_eq!(
+                converted.phoneme_index[..len],
+                phonemes.phoneme_index[..len],
+                "{}",
+                written
+            );
+            assert_eq!(converted.stress[..len], phonemes.stress[..len]);
+            assert_eq!(
+                converted.phoneme_length[..len],
+                phonemes.phoneme_length[..len]
+            );
+        }
+        assert_eq!(convert_phonemes(b" AW ").to_phonetic_string(), " AW ");
+    }
+
+    #[test]
+    fn liquid_length() {
+        // A vowel at the end of the input has no phoneme after it.
+        assert_eq!(convert_phonemes(b"AA").phoneme_index[..2], [9, 255]);
+
+        // 'AA' 'RX' 'K'
+        let mut rules = PhonemeRuleSet::default();
+        let shortened = convert_phonemes_with(b" AARXK ", &rules);
+        assert_eq!(shortened.phoneme_index[1..3], [9, 18]);
+        rules.set_enabled(PhonemeRule::LiquidLength, false);
+        let phonemes = convert_phonemes_with(b" AARXK ", &rules);
+        assert_eq!(shortened.phoneme_length[1] + 1, phonemes.phoneme_length[1]);
+    }
 }
@@ -0,0 +1,95 @@
+use crate::{
+    frames::{SAMPLE_RATE, TICK_SAMPLES},
+    Params,
+};
+
+// PITCH UNITS
+//
+// `Params::pitch` is the length of a glottal pulse in ticks of the render
+// loop, and one tick lasts 162/50 samples at 22050 Hz. The fundamental
+// frequency is therefore
+//
+//     f0 = 22050 / (3.24 * pitch) = 6806 / pitch Hz
+//
+// so the default pitch of 64 is about 106 Hz, and a lower value gives a
+// higher voice. Whole pitch values are coarse at the top of the range:
+// below pitch 18 (about 380 Hz) one step is more than a semitone.
+//
+// The value set in `Params` is the pitch of unstressed phonemes in sing
+// mode. The stress digits change it by -32 to +12 units with the default
+// `intonation`, and outside sing mode the pitch also follows the first
+// formant.
+
+/// Standard tuning, A4 = MIDI note 69 = 440 Hz.
+const A4_HZ: f64 = 440.0;
+const A4_NOTE: f64 = 69.0;
+
+/// Fundamental frequency of a pitch value.
+pub fn pitch_to_hz(pitch: u8) -> f64 {
+    SAMPLE_RATE as f64 / TICK_SAMPLES / pitch.max(1) as f64
+}
+
+/// Nearest pitch value for a frequency, limited to 27-6806 Hz, the range
+/// of pitch values 255 to 1.
+pub fn hz_to_pitch(hz: f64) -> u8 {
+    let pitch = SAMPLE_RATE as f64 / TICK_SAMPLES / hz;
+    if pitch.is_nan() {
+        return 255;
+    }
+    pitch.round().clamp(1.0, 255.0) as u8
+}
+
+pub fn midi_note_to_hz(note: u8) -> f64 {
+    A4_HZ * 2f64.powf((note as f64 - A4_NOTE) / 12.0)
+}
+
+/// MIDI note number of a frequency, with cents as the fractional part.
+pub fn hz_to_midi_note(hz: f64) -> f64 {
+    A4_NOTE + 12.0 * (hz / A4_HZ).log2()
+}
+
+/// Nearest pitch value for a MIDI note. Notes 21 (A0) to 116 (G#8) are in
+/// range.
+pub fn midi_note_to_pitch(note: u8) -> u8 {
+    hz_to_pitch(midi_note_to_hz(note))
+}
+
+/// MIDI note number of a pitch value, with cents as the fractional part.
+pub fn pitch_to_midi_note(pitch: u8) -> f64 {
+    hz_to_midi_note(pitch_to_hz(pitch))
+}
+
+impl Params {
+    /// Set the pitch to the nearest value for a frequency in Hz.
+    pub fn with_pitch_hz(mut self, hz: f64) -> Self {
+        self.pitch = hz_to_pitch(hz);
+        self
+    }
+
+    /// Set the pitch to the nearest value for a MIDI note number.
+    pub fn with_pitch_note(mut self, note: u8) -> Self {
+        self.pitch = midi_note_to_pitch(note);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pitch::{hz_to_pitch, midi_note_to_pitch, pitch_to_hz, pitch_to_midi_note},
+        Params,
+    };
+
+    #[test]
+    fn conversions() {
+        assert_eq!(pitch_to_hz(64).round(), 106.0);
+        assert_eq!(hz_to_pitch(pitch_to_hz(64)), 64);
+        assert_eq!(hz_to_pitch(0.0), 255);
+        assert_eq!(hz_to_pitch(1e6), 1);
+
+        // A3 = 220 Hz
+        assert_eq!(midi_note_to_pitch(57), 31);
+        assert!((pitch_to_midi_note(31) - 57.0).abs() < 0.2);
+        assert_eq!(Params::default().with_pitch_hz(220.0).pitch, 31);
+    }
+}
@@ -0,0 +1,899 @@
+use std::{
+    fmt,
+    ops::Range,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    language::Language,
+    lexicon::Lexicon,
+    normalize::Normalizer,
+    transliterate::{transliterate_char, Unsupported},
+    validate::validate_phonetic,
+};
+
+// RECITER
+//
+// Converts text to phonetic input, a port of reciter.c.
+//
+// The text is read from left to right. At every position, the rules for
+// the current character are tried in order, and the first one that matches
+// produces the phonemes and moves past the matched text. A rule is written
+//
+//     prefix(match)suffix=phonemes
+//
+// where the match must equal the text at the current position, and the
+// prefix and suffix describe the text before and after it. Letters in the
+// prefix and suffix must be equal to the text, while the other symbols
+// stand for classes of characters:
+//
+//     ' '  a character that is not a letter
+//     '#'  a vowel: A E I O U Y
+//     '.'  a voiced consonant: B D G J L M N R V W Z
+//     '&'  a sibilant: C G J S X Z, or CH or SH before the match
+//     '@'  a consonant that changes a following long U: D J L N R S T Z
+//     '^'  a consonant
+//     '+'  E, I or Y
+//     ':'  zero or more consonants
+//     '%'  a suffix: E, ER, ES, ED, ELY, EFUL or ING (only after the match)
+//
+// For example, "#:(AL) =UL" reads "AL" at the end of a word, after a vowel
+// and any number of consonants, like the end of "METAL".
+//
+// The rules are read from rule files, with one rule in quotes on each line
+// and comments starting with "#":
+//
+//     # A
+//     " (AR)O=AXR"
+//     "(A)=AE"
+//
+// Letters outside A to Z are spelled with letters the rules know, by
+// lines without quotes. Both cases of the letter are replaced before the
+// rules are applied:
+//
+//     Å=AA
+//
+// The English rules of the C version are in rules/english.rules, and
+// there are rules for other languages, see `Language`. A `RuleSet` from
+// another file can replace them, or be tried before them.
+//
+// Characters without rules, such as brackets and line breaks, separate
+// words. A period is a pause, except in front of a digit, where it is read
+// as "POINT".
+//
+// Unlike the C version, the input and output are not limited to 256
+// characters, letters outside ASCII are read as in `transliterate`, words
+// can be looked up in a `Lexicon` first, and numbers and abbreviations can
+// be written as words first, see `normalize`.
+//
+// Acronyms and numbers such as phone numbers can also be read character
+// by character, see `ReadingMode`.
+
+/// Convert English text to phonetic input for `convert_phonemes`.
+pub fn text_to_phonemes(text: &str) -> String {
+    recite(text, built_in(Language::English), &Lexicon::new())
+}
+
+/// Converts text with a set of rules, after looking words up in a lexicon.
+#[derive(Clone, Debug)]
+pub struct Reciter {
+    pub rules: RuleSet,
+    pub lexicon: Lexicon,
+    /// Writes numbers, dates and abbreviations as words before the rules
+    /// read them. Without it, they are read by the rules as in the C
+    /// version.
+    pub normalizer: Option<Normalizer>,
+}
+
+impl Default for Reciter {
+    fn default() -> Self {
+        Self::for_language(Language::English)
+    }
+}
+
+impl Reciter {
+    pub fn new(rules: RuleSet) -> Self {
+        Self {
+            rules,
+            lexicon: Lexicon::new(),
+            normalizer: Some(Normalizer::default()),
+        }
+    }
+
+    /// The built-in rules and normalizer for a language.
+    pub fn for_language(language: Language) -> Self {
+        Self {
+            rules: RuleSet::for_language(language),
+            lexicon: Lexicon::new(),
+            normalizer: Some(Normalizer::for_language(language)),
+        }
+    }
+
+    /// Convert text to phonetic input for `convert_phonemes`. Words found
+    /// in the lexicon are pronounced as it says instead of by the rules.
+    ///
+    /// Parts of the text can be read in another mode with inline markup,
+    /// e.g. "{spell=HTTP}" or "{digits=555-0123}".
+    pub fn text_to_phonemes(&self, text: &str) -> String {
+        let mut output = String::new();
+        let mut rest = text;
+        while let Some((range, mode, inner)) = markup(rest) {
+            output.push_str(&self.words(&rest[..range.start]));
+            output.push_str(&self.read(inner, mode));
+            rest = &rest[range.end..];
+        }
+        output.push_str(&self.words(rest));
+        output
+    }
+
+    /// How `text_to_phonemes` reads text without markup, step by step.
+    /// The phonemes of the steps make up its phonetic input.
+    pub fn explain(&self, text: &str) -> Vec<Step> {
+        let text = match &self.normalizer {
+            Some(normalizer) => normalizer.normalize(text),
+            None => text.to_string(),
+        };
+        let mut steps = Vec::new();
+        recite_with(&text, &self.rules, &self.lexicon, |step| steps.push(step));
+        steps
+    }
+
+    /// Convert text to phonetic input, read as `mode` says.
+    pub fn read(&self, text: &str, mode: ReadingMode) -> String {
+        match mode {
+            ReadingMode::Words => self.text_to_phonemes(text),
+            ReadingMode::Spell => self.characters(text),
+            ReadingMode::Digits => {
+                let mut output = String::new();
+                let mut rest = text;
+                while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+                    let end = rest[start..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .map_or(rest.len(), |length| start + length);
+                    output.push_str(&self.words(&rest[..start]));
+                    output.push_str(&self.characters(&rest[start..end]));
+                    rest = &rest[end..];
+                }
+                output.push_str(&self.words(rest));
+                output
+            }
+        }
+    }
+
+    fn words(&self, text: &str) -> String {
+        match &self.normalizer {
+            Some(normalizer) => recite(&normalizer.normalize(text), &self.rules, &self.lexicon),
+            None => recite(text, &self.rules, &self.lexicon),
+        }
+    }
+
+    /// The names of the characters, with short pauses between them.
+    /// Letters outside A to Z that the rules spell, digits and punctuation
+    /// are read by the rules alone.
+    fn characters(&self, text: &str) -> String {
+        let read = |c: char| recite(c.encode_utf8(&mut [0; 4]), &self.rules, &Lexicon::new());
+        let mut names = Vec::new();
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            let ascii = match self.rules.spelling(c) {
+                Some(_) => None,
+                None if c.is_ascii() => Some(c.to_string()),
+                None => transliterate_char(c),
+            };
+            let Some(ascii) = ascii else {
+                names.push(read(c));
+                continue;
+            };
+            for c in ascii.chars().map(|c| c.to_ascii_uppercase()) {
+                names.push(match c {
+                    'A'..='Z' => LETTER_NAMES[(c as u8 - b'A') as usize].to_string(),
+                    c => read(c),
+                });
+            }
+        }
+        let names = names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        format!(" {} ", names.join(" - "))
+    }
+
+    /// The characters in `text` that `text_to_phonemes` skips, since
+    /// neither the rules nor transliteration spell them.
+    pub fn unsupported(&self, text: &str) -> Vec<Unsupported> {
+        text.char_indices()
+            .filter(|(_, c)| !c.is_ascii())
+            .filter(|(_, c)| self.rules.spelling(*c).is_none() && transliterate_char(*c).is_none())
+            // The normalizer reads these next to numbers.
+            .filter(|(_, c)| self.normalizer.is_none() || !matches!(c, '€' | '£' | '¥'))
+            .map(|(offset, symbol)| Unsupported { offset, symbol })
+            .collect()
+    }
+}
+
+/// How text is read, see `Reciter::read`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadingMode {
+    /// Word by word.
+    #[default]
+    Words,
+    /// Character by character, e.g. acronyms such as "HTTP".
+    Spell,
+    /// Numbers digit by digit, e.g. serial and phone numbers, and the rest
+    /// word by word.
+    Digits,
+}
+
+/// The first inline markup for a reading mode in the text, e.g.
+/// "{spell=HTTP}": its byte range, the mode and the text it applies to.
+fn markup(text: &str) -> Option<(Range<usize>, ReadingMode, &str)> {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find('{').map(|idx| offset + idx) {
+        let end = start + text[start..].find('}')? + 1;
+        offset = start + 1;
+        let Some((key, inner)) = text[start + 1..end - 1].split_once('=') else {
+            continue;
+        };
+        let mode = match key.trim().to_ascii_lowercase().as_str() {
+            "spell" => ReadingMode::Spell,
+            "digits" => ReadingMode::Digits,
+            _ => continue,
+        };
+        return Some((start..end, mode, inner));
+    }
+    None
+}
+
+fn recite(text: &str, rules: &RuleSet, lexicon: &Lexicon) -> String {
+    recite_with(text, rules, lexicon, |_| {})
+}
+
+/// How the reciter read a part of the text, see `Reciter::explain`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The text read, as the rules see it.
+    pub text: String,
+    pub phonemes: String,
+    pub origin: Origin,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// A rule, written as in its rule file.
+    Rule {
+        rule: String,
+        /// The rule file, see `RuleSet::named`.
+        source: String,
+        /// Line number in the rule file, from 1.
+        line: usize,
+    },
+    Lexicon,
+    /// A period read as a pause, or a character without rules read as a
+    /// space.
+    Punctuation,
+}
+
+fn recite_with(
+    text: &str,
+    rules: &RuleSet,
+    lexicon: &Lexicon,
+    mut step: impl FnMut(Step),
+) -> String {
+    let mut record = |input: &[u8], phonemes: &str, origin| {
+        step(Step {
+            text: String::from_utf8_lossy(input).into_owned(),
+            phonemes: phonemes.to_string(),
+            origin,
+        })
+    };
+
+    // A space in front, so that rules can match the start of the first word.
+    let mut input = vec![b' '];
+    for c in text.chars() {
+        if c.is_ascii() {
+            input.push(uppercase(c as u8));
+        } else if let Some(spelling) = rules.spelling(c) {
+            input.extend(spelling.bytes());
+        } else if let Some(ascii) = transliterate_char(c) {
+            input.extend(ascii.bytes().map(uppercase));
+        } else {
+            input.push(b' ');
+        }
+    }
+
+    let mut output = String::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let c = input[pos];
+        if c == b'.' && flags(char_at(&input, pos as isize + 1)) & 1 == 0 {
+            output.push('.');
+            record(&input[pos..pos + 1], ".", Origin::Punctuation);
+            pos += 1;
+            continue;
+        }
+
+        // Whole words in the lexicon
+        if flags(c) & 128 != 0 && flags(char_at(&input, pos as isize - 1)) & 128 == 0 {
+            let length = input[pos..]
+                .iter()
+                .take_while(|c| flags(**c) & 128 != 0)
+                .count();
+            let word = String::from_utf8_lossy(&input[pos..pos + length]);
+            if let Some(phonemes) = lexicon.get(&word) {
+                output.push_str(phonemes);
+                record(&input[pos..pos + length], phonemes, Origin::Lexicon);
+                pos += length;
+                continue;
+            }
+        }
+
+        // Digits, punctuation and letters have rules.
+        if flags(c) & (2 | 128) == 0 {
+            record(&input[pos..pos + 1], " ", Origin::Punctuation);
+            input[pos] = b' ';
+            output.push(' ');
+            pos += 1;
+            continue;
+        }
+
+        match rules.apply(&input, pos) {
+            Some(rule) => {
+                let length = rule.pattern.len();
+                output.push_str(&rule.phonemes);
+                let origin = Origin::Rule {
+                    rule: rule.to_string(),
+                    source: rule.source.to_string(),
+                    line: rule.line,
+                };
+                record(&input[pos..pos + length], &rule.phonemes, origin);
+                pos += length;
+            }
+            None => pos += 1,
+        }
+    }
+
+    output
+}
+
+/// Phonetic names of the letters, for spelling words out.
+const LETTER_NAMES: [&str; 26] = [
+    "EY4",
+    "BIY4",
+    "SIY4",
+    "DIY4",
+    "IY4",
+    "EH4F",
+    "JIY4",
+    "EY4CH",
+    "AY4",
+    "JEY4",
+    "KEY4",
+    "EH4L",
+    "EH4M",
+    "EH4N",
+    "OH4W",
+    "PIY4",
+    "KYUW4",
+    "AA5R",
+    "EH4S",
+    "TIY4",
+    "YUW4",
+    "VIY4",
+    "DAH4BULYUW",
+    "EH4KS",
+    "WAY4",
+    "ZIY4",
+];
+
+/// Same mapping to upper case as the C version, which also folds some
+/// symbols, e.g. '{' to '['.
+fn uppercase(c: u8) -> u8 {
+    if c >= 112 {
+        c & 95
+    } else if c >= 96 {
+        c & 79
+    } else {
+        c
+    }
+}
+
+/// Character classes, see `flags`.
+//tab36376
+#[rustfmt::skip]
+const CHAR_FLAGS: [u8; 96] = [
+    0, 0, 0, 0, 0, 0, 0, 0, // 0-7
+    0, 0, 0, 0, 0, 0, 0, 0, // 8-15
+    0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0,
+    0, 2, 2, 2, 2, 2, 2, 130, // ' ', '!'
+    0, 0, 2, 2, 2, 2, 2, 2,
+    3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 2, 2, 2, 2, 2, 2,
+    2, 192, 168, 176, 172, 192, 160, 184, // '@', 'A'
+    160, 192, 188, 160, 172, 168, 172, 192,
+    160, 160, 172, 180, 164, 192, 168, 168,
+    176, 192, 188, 0, 0, 0, 2, 0, // 'X', 'Y', 'Z', '['
+];
+
+/// Character classes used by the rules:
+///
+/// ```text
+/// 1    digit
+/// 2    digit or punctuation, has rules
+/// 4    consonant before long U ('@')
+/// 8    voiced consonant ('.')
+/// 16   sibilant ('&')
+/// 32   consonant ('^')
+/// 64   vowel ('#')
+/// 128  letter, has rules
+/// ```
+fn flags(c: u8) -> u8 {
+    CHAR_FLAGS.get(c as usize).copied().unwrap_or(0)
+}
+
+/// Character at a position, where the text is surrounded by characters
+/// that are not letters.
+fn char_at(input: &[u8], pos: isize) -> u8 {
+    usize::try_from(pos)
+        .ok()
+        .and_then(|pos| input.get(pos))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// The rules for a language, parsed once.
+fn built_in(language: Language) -> &'static RuleSet {
+    static RULES: [OnceLock<RuleSet>; Language::ALL.len()] =
+        [const { OnceLock::new() }; Language::ALL.len()];
+    RULES[language as usize].get_or_init(|| {
+        let text = match language {
+            Language::English => include_str!("rules/english.rules"),
+            Language::Norwegian => include_str!("rules/norwegian.rules"),
+            Language::German => include_str!("rules/german.rules"),
+            Language::Spanish => include_str!("rules/spanish.rules"),
+        };
+        let source = format!("{}.rules", language.name());
+        RuleSet::parse(text)
+            .expect("invalid built-in rules")
+            .named(&source)
+    })
+}
+
+/// Letter-to-sound rules, read from a rule file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    /// Letters outside A to Z, and how they are spelled for the rules.
+    spellings: Vec<(char, String)>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// A line of a rule file that couldn't be read.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RuleError {
+    /// Line number, from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl RuleSet {
+    /// The English rules of the C version.
+    pub fn english() -> Self {
+        Self::for_language(Language::English)
+    }
+
+    /// The built-in rules for a language.
+    pub fn for_language(language: Language) -> Self {
+        built_in(language).clone()
+    }
+
+    /// Read a rule file.
+    pub fn parse(text: &str) -> Result<Self, RuleError> {
+        let mut rules = Vec::new();
+        let mut spellings = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let error = |message| RuleError {
+                line: idx + 1,
+                message,
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !line.starts_with('"') && line.contains('=') {
+                spellings.push(parse_spelling(line).map_err(error)?);
+                continue;
+            }
+            let rule = unquote(line).and_then(|rule| Rule::parse(&rule, idx + 1));
+            rules.push(rule.map_err(error)?);
+        }
+        Ok(Self { rules, spellings })
+    }
+
+    /// Name the file the rules were read from, for `Reciter::explain`.
+    pub fn named(mut self, source: &str) -> Self {
+        let source = Arc::<str>::from(source);
+        for rule in &mut self.rules {
+            rule.source = source.clone();
+        }
+        self
+    }
+
+    /// Add rules that are tried before the rules in the set, to change
+    /// the pronunciation of some spellings and keep the rest.
+    pub fn prepend(&mut self, mut rules: RuleSet) {
+        rules.rules.append(&mut self.rules);
+        self.rules = rules.rules;
+        rules.spellings.append(&mut self.spellings);
+        self.spellings = rules.spellings;
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// How a letter outside A to Z is spelled for the rules, if it is.
+    fn spelling(&self, c: char) -> Option<&str> {
+        self.spellings
+            .iter()
+            .find(|(letter, _)| *letter == c || letter.to_lowercase().eq(c.to_lowercase()))
+            .map(|(_, spelling)| spelling.as_str())
+    }
+
+    /// The first rule that matches at `pos`.
+    fn apply(&self, input: &[u8], pos: usize) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.pattern.first() == input.get(pos))
+            .find(|rule| rule.matches(input, pos))
+    }
+}
+
+/// Read the spelling of a letter outside A to Z, e.g. "Å=AA".
+fn parse_spelling(line: &str) -> Result<(char, String), String> {
+    let (letter, spelling) = line.split_once('=').unwrap_or_default();
+    let mut letters = letter.trim().chars();
+    let (Some(letter), None) = (letters.next(), letters.next()) else {
+        return Err("a spelling is for a single letter".to_string());
+    };
+    if letter.is_ascii() {
+        return Err(format!("'{}' is spelled by the rules already", letter));
+    }
+    let spelling = spelling.trim();
+    if spelling.is_empty()
+        || !spelling
+            .bytes()
+            .all(|c| c.is_ascii_uppercase() || c == b'\'')
+    {
+        return Err(format!(
+            "\"{}\" is not spelled in upper case letters",
+            spelling
+        ));
+    }
+    Ok((letter, spelling.to_string()))
+}
+
+/// Read a rule in quotes, where a quote or backslash in the rule is
+/// written with a backslash in front.
+fn unquote(line: &str) -> Result<String, String> {
+    let inner = line
+        .strip_prefix('"')
+        .and_then(|line| line.strip_suffix('"'))
+        .ok_or("a rule must be in quotes")?;
+    let mut rule = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => rule.push(chars.next().ok_or("a rule must be in quotes")?),
+            '"' => return Err("a quote in a rule must be written \\\"".to_string()),
+            c => rule.push(c),
+        }
+    }
+    Ok(rule)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    prefix: Vec<u8>,
+    pattern: Vec<u8>,
+    suffix: Vec<u8>,
+    phonemes: String,
+    /// Where the rule was read, for `Reciter::explain`.
+    source: Arc<str>,
+    line: usize,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        write!(
+            f,
+            "{}({}){}={}",
+            text(&self.prefix),
+            text(&self.pattern),
+            text(&self.suffix),
+            self.phonemes
+        )
+    }
+}
+
+impl Rule {
+    fn parse(rule: &str, line: usize) -> Result<Self, String> {
+        if !rule.is_ascii() {
+            return Err("a rule must be ASCII".to_string());
+        }
+        let (prefix, rest) = rule.split_once('(').ok_or("missing (")?;
+        let (pattern, rest) = rest.split_once(')').ok_or("missing )")?;
+        let (suffix, phonemes) = rest.split_once('=').ok_or("missing =")?;
+
+        if pattern.is_empty() {
+            return Err("nothing to match between ( and )".to_string());
+        }
+        if let Some(c) = pattern.bytes().find(|c| uppercase(*c) != *c) {
+            return Err(format!("'{}' in the match is not upper case", c as char));
+        }
+        let is_symbol = |c: u8, symbols: &str| flags(c) & 128 != 0 || symbols.contains(c as char);
+        if let Some(c) = prefix.bytes().find(|c| !is_symbol(*c, " #.&@^+:")) {
+            return Err(format!("'{}' can't be used in a prefix", c as char));
+        }
+        if let Some(c) = suffix.bytes().find(|c| !is_symbol(*c, " #.&@^+:%")) {
+            return Err(format!("'{}' can't be used in a suffix", c as char));
+        }
+        if let Some(diagnostic) = validate_phonetic(phonemes).first() {
+            return Err(format!("{}: {}", phonemes, diagnostic.message));
+        }
+
+        Ok(Rule {
+            prefix: prefix.as_bytes().to_vec(),
+            pattern: pattern.as_bytes().to_vec(),
+            suffix: suffix.as_bytes().to_vec(),
+            phonemes: phonemes.to_string(),
+            source: Arc::from(""),
+            line,
+        })
+    }
+
+    /// Whether the rule matches at `pos`.
+    fn matches(&self, input: &[u8], pos: usize) -> bool {
+        let end = pos + self.pattern.len();
+        input.get(pos..end) == Some(self.pattern.as_slice())
+            && self.matches_prefix(input, pos as isize)
+            && self.matches_suffix(input, end as isize - 1)
+    }
+
+    /// Match the prefix from right to left, `pos` is the first character
+    /// after it.
+    fn matches_prefix(&self, input: &[u8], mut pos: isize) -> bool {
+        for &symbol in self.prefix.iter().rev() {
+            let prev = char_at(input, pos - 1);
+            if flags(symbol) & 128 != 0 {
+                if prev != symbol {
+                    return false;
+                }
+                pos -= 1;
+                continue;
+            }
+
+            match symbol {
+                b' ' if flags(prev) & 128 == 0 => pos -= 1,
+                b'#' if flags(prev) & 64 != 0 => pos -= 1,
+                b'.' if flags(prev) & 8 != 0 => pos -= 1,
+                b'&' if flags(prev) & 16 != 0 => pos -= 1,
+                b'&' if prev == b'H' && matches!(char_at(input, pos - 2), b'C' | b'S') => pos -= 2,
+                // The C version also tries TH, CH and SH here, but compares
+                // the wrong character, so they never match.
+                b'@' if flags(prev) & 4 != 0 => pos -= 1,
+                b'^' if flags(prev) & 32 != 0 => pos -= 1,
+                b'+' if matches!(prev, b'E' | b'I' | b'Y') => pos -= 1,
+                b':' => {
+                    while flags(char_at(input, pos - 1)) & 32 != 0 {
+                        pos -= 1;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Match the suffix from left to right, `pos` is the last character
+    /// before it.
+    fn matches_suffix(&self, input: &[u8], mut pos: isize) -> bool {
+        for &symbol in &self.suffix {
+            let next = char_at(input, pos + 1);
+            if flags(symbol) & 128 != 0 {
+                if next != symbol {
+                    return false;
+                }
+                pos += 1;
+                continue;
+            }
+
+            match symbol {
+                b' ' if flags(next) & 128 == 0 => pos += 1,
+                b'#' if flags(next) & 64 != 0 => pos += 1,
+                b'.' if flags(next) & 8 != 0 => pos += 1,
+                b'&' if flags(next) & 16 != 0 => pos += 1,
+                // Mirrors the prefix, so this looks for HC and HS, as the C
+                // version does.
+                b'&' if next == b'H' && matches!(char_at(input, pos + 2), b'C' | b'S') => pos += 2,
+                b'@' if flags(next) & 4 != 0 => pos += 1,
+                b'^' if flags(next) & 32 != 0 => pos += 1,
+                b'+' if matches!(next, b'E' | b'I' | b'Y') => pos += 1,
+                b':' => {
+                    while flags(char_at(input, pos + 1)) & 32 != 0 {
+                        pos += 1;
+                    }
+                }
+                b'%' => match suffix_length(input, pos + 1) {
+                    Some(length) => pos += length,
+                    None => return false,
+                },
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Length of the suffix matched by '%' at `pos`: E, ER, ES, ED, ELY, EFUL
+/// or ING. A lone E must end the word, the others may be followed by more
+/// letters.
+fn suffix_length(input: &[u8], pos: isize) -> Option<isize> {
+    let at = |offset: isize| char_at(input, pos + offset);
+    match at(0) {
+        b'E' if flags(at(1)) & 128 == 0 => Some(1),
+        b'E' => match at(1) {
+            b'R' | b'S' | b'D' => Some(2),
+            b'L' if at(2) == b'Y' => Some(3),
+            b'F' if at(2) == b'U' && at(3) == b'L' => Some(4),
+            _ => None,
+        },
+        b'I' if at(1) == b'N' && at(2) == b'G' => Some(3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        language::Language,
+        reciter::{text_to_phonemes, ReadingMode, Reciter, RuleSet},
+    };
+
+    #[test]
+    fn same_as_c_version() {
+        assert_eq!(text_to_phonemes("Hello world"), " /HEHLOW WERLD");
+        assert_eq!(
+            text_to_phonemes("the quick brown fox"),
+            " DHAX KWIHK BROWN FAAKS"
+        );
+        assert_eq!(
+            text_to_phonemes("3.5 dollars, 1st!"),
+            "  THRIY4 POYNT FAY4V DAALAA5RZ, FER4ST."
+        );
+        // Symbols are folded like lower case letters.
+        assert_eq!(text_to_phonemes("a|b`"), text_to_phonemes("A\\B@"));
+    }
+
+    #[test]
+    fn reading_modes() {
+        let reciter = Reciter::default();
+        assert_eq!(
+            reciter.read("HTTP", ReadingMode::Spell),
+            " EY4CH - TIY4 - TIY4 - PIY4 "
+        );
+        assert_eq!(
+            reciter.read("room 12", ReadingMode::Digits),
+            " RUW5M  WAH4N - TUW4  "
+        );
+        let phonetic = reciter.text_to_phonemes("{spell=Ax} {digits=20}");
+        assert_eq!(
+            phonetic.split_whitespace().collect::<Vec<_>>(),
+            ["EY4", "-", "EH4KS", "TUW4", "-", "ZIY4ROW"]
+        );
+        assert_eq!(
+            Reciter::for_language(Language::German).read("7", ReadingMode::Spell),
+            " ZIY4BUN "
+        );
+    }
+
+    #[test]
+    fn lexicon_words() {
+        let mut reciter = Reciter::default();
+        reciter.lexicon.insert("sam", "SAE4M");
+        reciter.lexicon.insert("don't", "DOW4NT");
+        assert_eq!(
+            reciter.text_to_phonemes("Sam, don't sample"),
+            " SAE4M, DOW4NT SAEMPUL"
+        );
+    }
+
+    #[test]
+    fn rule_files() {
+        let mut rules = RuleSet::parse("# test\n\" (PH)=F\"\n\"(\\\")=KWOWT\"\n").unwrap();
+        assert_eq!(rules.len(), 2);
+        rules.prepend(RuleSet::parse("\"(PH)O=P\"").unwrap());
+        let reciter = Reciter::new(rules);
+        assert_eq!(reciter.text_to_phonemes("ph \"pho"), " F KWOWTP");
+
+        let mut english = RuleSet::english();
+        english.prepend(RuleSet::parse("\" (GH)=G\"").unwrap());
+        assert_eq!(Reciter::new(english).text_to_phonemes("ghost"), " GOWST");
+
+        for (rule, line) in [
+            ("(A=AE", 1),
+            ("\n\"(A)x=AE\"", 2),
+            ("\"(a)=AE\"", 1),
+            ("\"(A)=AEE\"", 1),
+        ] {
+            assert_eq!(RuleSet::parse(rule).unwrap_err().line, line);
+        }
+    }
+
+    #[test]
+    fn unicode_text() {
+        let reciter = Reciter::default();
+        assert_eq!(
+            reciter.text_to_phonemes("naïve café"),
+            reciter.text_to_phonemes("naive cafe")
+        );
+        assert_eq!(
+            reciter.text_to_phonemes("don’t"),
+            reciter.text_to_phonemes("don't")
+        );
+        assert_eq!(reciter.read("é", ReadingMode::Spell), " IY4 ");
+
+        let unsupported = reciter.unsupported("5 € for ☃ or ø");
+        assert_eq!(
+            unsupported.iter().map(|u| u.symbol).collect::<String>(),
+            "☃"
+        );
+        assert_eq!(
+            Reciter::for_language(Language::German)
+                .unsupported("ø")
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn languages() {
+        let recite = |language, text| Reciter::for_language(language).text_to_phonemes(text);
+        assert_eq!(
+            recite(Language::Norwegian, "Kjøp blåbær"),
+            " /HYER4P BLAO4BAER"
+        );
+        assert_eq!(
+            recite(Language::German, "Schöne Grüße"),
+            " SHER4NAX GRUX4SAX"
+        );
+        assert_eq!(
+            recite(Language::Spanish, "Mañana, canción"),
+            " MAANYAA4NAA, KAANSYOH4N"
+        );
+
+        let rules = RuleSet::parse("Ø=OE\n\"(OE)=ER\"").unwrap();
+        assert_eq!(Reciter::new(rules).text_to_phonemes("ø Ø"), " ER ER");
+        assert_eq!(RuleSet::parse("\n\nØ=oe").unwrap_err().line, 3);
+        assert!(RuleSet::parse("O=OE").is_err());
+    }
+}
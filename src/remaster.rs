@@ -0,0 +1,259 @@
+use crate::{
+    frames::{FramesTables, SAMPLE_RATE, TICK_SAMPLES},
+    tables::{SAMPLE_OFF_LEVEL, SAMPLE_TABLE},
+};
+
+// FLOATING-POINT FORMANT RENDERER
+//
+// An alternative to `mk_wav` that reads the same frames, but renders them
+// with floating-point oscillators at any sample rate:
+//
+// - The two sine formants are computed directly instead of through the
+//   8-bit SINE table, and the third formant is a band-limited (PolyBLEP)
+//   rectangle wave instead of the RECTANGLE table.
+//
+// - Amplitudes and frequencies are interpolated sample by sample between
+//   frames instead of switching in steps of 4-bit values.
+//
+// - Each glottal pulse is faded in and out over a fraction of a millisecond,
+//   to remove the clicks from resetting the oscillators.
+//
+// The timing is the same as on the C64: a glottal pulse lasts `pitch`
+// ticks of the render loop and a frame lasts `speed` ticks, so the output
+// keeps the rhythm and intonation of the classic renderer.
+
+/// Output level of one step of formant amplitude, chosen so that the
+/// loudness matches `mk_wav`, where a sample is `SINE * amplitude / 32`.
+const AMPLITUDE_GAIN: f32 = 127.0 / 32.0 / 128.0;
+
+/// The RECTANGLE table swings between -112 and 112, while SINE peaks at 127.
+const RECTANGLE_LEVEL: f32 = 112.0 / 127.0;
+
+/// Duration of the fade at both ends of each glottal pulse, in ticks.
+const PULSE_TAPER_TICKS: f64 = 2.0;
+
+/// Bit rates of the sampled consonants, measured in samples at `SAMPLE_RATE`
+/// per bit. The C version writes unvoiced bits 60/50 samples apart, and
+/// voiced bits 54/50 samples apart.
+const UNVOICED_BIT_SAMPLES: f64 = 60.0 / 50.0;
+const VOICED_BIT_SAMPLES: f64 = 54.0 / 50.0;
+
+/// Render frames to floating-point samples in the range [-1, 1].
+pub fn render(frames: &FramesTables, sample_rate: u32) -> Vec<f32> {
+    let mut output = Vec::new();
+    let ticks_per_sample = SAMPLE_RATE as f64 / TICK_SAMPLES / sample_rate as f64;
+    let cycles_per_tick_unit = 1.0 / 256.0;
+    let sum_length = frames.sum_length as usize;
+
+    // Smooths the sampled consonant bit streams, which would otherwise
+    // alias at high output rates.
+    let mut noise_filter = OnePole::new(8000.0, sample_rate);
+
+    let mut frame_idx = 0;
+    let mut frame_pos = 0.0;
+    let mut pulse_pos = 0.0;
+    let mut pulse_length = frames.pitches[0].max(1) as f64;
+    let mut phases = [0.0f64; 3];
+    let mut voiced_sample_pos = 0.0f64;
+
+    while frame_idx < sum_length {
+        let consonant_flag = frames.sampled_consonant_flag[frame_idx];
+
+        if consonant_flag & 248 != 0 {
+            // UNVOICED SAMPLE
+            //
+            // Plays the sample bits and skips two frames, like `mk_wav`.
+            let table = ((consonant_flag & 7) - 1) as usize;
+            let off_level = level((SAMPLE_OFF_LEVEL[table] & 0x0f) * 16);
+            let on_level = level(5 * 16);
+            let first_byte = ((consonant_flag & 248) ^ 255) as usize;
+            let bits = (256 - first_byte) * 8;
+            let samples_per_bit = UNVOICED_BIT_SAMPLES * sample_rate as f64 / SAMPLE_RATE as f64;
+            let samples = (bits as f64 * samples_per_bit) as usize;
+
+            for n in 0..samples {
+                let bit = ((n as f64 / samples_per_bit) as usize).min(bits - 1);
+                let byte = SAMPLE_TABLE[table * 256 + first_byte + bit / 8];
+                let value = if byte & (128 >> (bit % 8)) != 0 {
+                    on_level
+                } else {
+                    off_level
+                };
+                output.push(noise_filter.process(value));
+            }
+
+            frame_idx += 2;
+            frame_pos = 0.0;
+            pulse_pos = pulse_length;
+            continue;
+        }
+
+        if pulse_pos >= pulse_length {
+            // Start a new glottal pulse and reset the formant oscillators
+            // to keep them in sync with it.
+            pulse_pos -= pulse_length;
+            pulse_length = frames.pitches[frame_idx].max(1) as f64;
+            phases = [0.0; 3];
+        }
+
+        let voiced_length = pulse_length - (pulse_length / 4.0).floor();
+        let value = if consonant_flag != 0 && pulse_pos >= voiced_length {
+            // VOICED SAMPLE
+            //
+            // Voiced sampled phonemes interleave the sample with the glottal pulse.
+            let table = ((consonant_flag & 7) - 1) as usize;
+            let bit = voiced_sample_pos as usize;
+            let byte = SAMPLE_TABLE[table * 256 + (bit / 8) % 256];
+            let value = if byte & (128 >> (bit % 8)) != 0 {
+                level((26 & 0xf) * 16)
+            } else {
+                level((6 & 0xf) * 16)
+            };
+            voiced_sample_pos += SAMPLE_RATE as f64 / VOICED_BIT_SAMPLES / sample_rate as f64;
+            noise_filter.process(value)
+        } else {
+            // FORMANTS
+            let next_idx = (frame_idx + 1).min(255);
+            let t = (frame_pos / frames.speeds[frame_idx].max(1) as f64) as f32;
+            let lerp = |table: &[u8; 256]| {
+                table[frame_idx] as f32 * (1.0 - t) + table[next_idx] as f32 * t
+            };
+
+            let frequencies = [
+                lerp(&frames.frequency1),
+                lerp(&frames.frequency2),
+                lerp(&frames.frequency3),
+            ];
+            let amplitudes = [
+                lerp(&frames.amplitude1),
+                lerp(&frames.amplitude2),
+                lerp(&frames.amplitude3),
+            ];
+
+            let taper = (pulse_pos / PULSE_TAPER_TICKS)
+                .min((pulse_length - pulse_pos) / PULSE_TAPER_TICKS)
+                .clamp(0.0, 1.0) as f32;
+
+            let mut sum = 0.0;
+            for (formant, phase) in phases.iter_mut().enumerate() {
+                let increment =
+                    frequencies[formant] as f64 * cycles_per_tick_unit * ticks_per_sample;
+                if increment < 0.5 {
+                    let wave = if formant < 2 {
+                        (*phase as f32 * std::f32::consts::TAU).sin()
+                    } else {
+                        RECTANGLE_LEVEL * rectangle(*phase, increment)
+                    };
+                    sum += wave * amplitudes[formant] * AMPLITUDE_GAIN;
+                }
+                *phase = (*phase + increment).fract();
+            }
+
+            sum * taper
+        };
+        output.push(value.clamp(-1.0, 1.0));
+
+        pulse_pos += ticks_per_sample;
+        frame_pos += ticks_per_sample;
+        let speed = frames.speeds[frame_idx].max(1) as f64;
+        if frame_pos >= speed {
+            frame_pos -= speed;
+            frame_idx += 1;
+        }
+    }
+
+    output
+}
+
+/// Convert an 8-bit unsigned output level to the range [-1, 1].
+fn level(value: u8) -> f32 {
+    (value as f32 - 128.0) / 128.0
+}
+
+/// Band-limited rectangle wave, matching the RECTANGLE table: low for the
+/// first half of the period and high for the second half.
+fn rectangle(phase: f64, increment: f64) -> f32 {
+    let naive = if phase < 0.5 { -1.0 } else { 1.0 };
+    let falling_edge = poly_blep(phase, increment);
+    let rising_edge = poly_blep((phase + 0.5).fract(), increment);
+    (naive - falling_edge + rising_edge) as f32
+}
+
+/// Polynomial band-limited step, the correction to apply around a
+/// discontinuity at phase 0 of a unit step.
+fn poly_blep(phase: f64, increment: f64) -> f64 {
+    if phase < increment {
+        let t = phase / increment;
+        t + t - t * t - 1.0
+    } else if phase > 1.0 - increment {
+        let t = (phase - 1.0) / increment;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+struct OnePole {
+    coefficient: f32,
+    state: f32,
+}
+
+impl OnePole {
+    fn new(cutoff: f32, sample_rate: u32) -> Self {
+        let cutoff = cutoff.min(sample_rate as f32 * 0.45);
+        let coefficient = 1.0 - (-std::f32::consts::TAU * cutoff / sample_rate as f32).exp();
+        Self {
+            coefficient,
+            state: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        self.state += self.coefficient * (input - self.state);
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{frames::FramesTables, remaster::render};
+
+    /// A sustained 'AA' vowel.
+    fn vowel_frames(length: u8) -> FramesTables {
+        FramesTables {
+            pitches: [64; 256],
+            frequency1: [26; 256],
+            frequency2: [40; 256],
+            frequency3: [89; 256],
+            amplitude1: [15; 256],
+            amplitude2: [13; 256],
+            amplitude3: [1; 256],
+            sampled_consonant_flag: [0; 256],
+            speeds: [72; 256],
+            sum_length: length,
+        }
+    }
+
+    #[test]
+    fn duration_follows_speed_and_sample_rate() {
+        let mut frames = vowel_frames(10);
+        let classic = render(&frames, 22050).len() as f64;
+        // 10 frames of 72 ticks of 162/50 samples
+        assert!((classic - 10.0 * 72.0 * 3.24).abs() < 2.0);
+
+        let double_rate = render(&frames, 44100).len() as f64;
+        assert!((double_rate - 2.0 * classic).abs() < 3.0);
+
+        frames.speeds = [144; 256];
+        let slow = render(&frames, 22050).len() as f64;
+        assert!((slow - 2.0 * classic).abs() < 3.0);
+    }
+
+    #[test]
+    fn output_is_bounded_and_audible() {
+        let output = render(&vowel_frames(20), 48000);
+        assert!(output.iter().all(|s| (-1.0..=1.0).contains(s)));
+        let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.2);
+    }
+}
@@ -0,0 +1,144 @@
+// BAND-LIMITED RESAMPLING
+//
+// SAM renders at a fixed 22050 Hz. To deliver other output rates, the
+// rendered buffer is resampled with a windowed sinc interpolator:
+//
+//     y(t) = sum_k x[k] * fc * sinc(fc * (t - k)) * w((t - k) / half_width)
+//
+// where t is the output sample position measured in input samples,
+// fc is the cutoff relative to the input Nyquist frequency, and w is a
+// Kaiser window. When downsampling, fc is lowered to the output Nyquist
+// frequency so that no aliasing is introduced. When upsampling, fc is 1
+// and the filter only removes the spectral images.
+
+/// Number of zero crossings of the sinc kernel on each side of the
+/// interpolated position.
+const ZERO_CROSSINGS: usize = 32;
+
+/// Kaiser window shape, roughly 90 dB of stopband attenuation.
+const KAISER_BETA: f64 = 8.6;
+
+/// Resample a signal from `from_rate` to `to_rate` Hz.
+pub fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    assert!(
+        from_rate > 0 && to_rate > 0,
+        "sample rates must be positive"
+    );
+    if from_rate == to_rate || input.is_empty() {
+        return input.to_vec();
+    }
+
+    let output_len = (input.len() as u64 * to_rate as u64).div_ceil(from_rate as u64) as usize;
+    let step = from_rate as f64 / to_rate as f64;
+    let cutoff = (to_rate as f64 / from_rate as f64).min(1.0);
+    let half_width = ZERO_CROSSINGS as f64 / cutoff;
+    let window_norm = bessel_i0(KAISER_BETA);
+
+    (0..output_len)
+        .map(|out_idx| {
+            let t = out_idx as f64 * step;
+            let first = (t - half_width).ceil().max(0.0) as usize;
+            let last = ((t + half_width).floor() as usize).min(input.len() - 1);
+
+            let mut sum = 0.0;
+            for (k, sample) in input.iter().enumerate().take(last + 1).skip(first) {
+                let x = t - k as f64;
+                let window = {
+                    let r = x / half_width;
+                    bessel_i0(KAISER_BETA * (1.0 - r * r).max(0.0).sqrt()) / window_norm
+                };
+                sum += *sample as f64 * cutoff * sinc(cutoff * x) * window;
+            }
+            sum as f32
+        })
+        .collect()
+}
+
+/// Resample an 8-bit unsigned buffer, as produced by `mk_wav`.
+pub fn resample_u8(input: &[u8], from_rate: u32, to_rate: u32) -> Vec<u8> {
+    let signal = u8_to_f32(input);
+    f32_to_u8(&resample(&signal, from_rate, to_rate))
+}
+
+/// Convert 8-bit unsigned samples to the range [-1, 1].
+pub fn u8_to_f32(input: &[u8]) -> Vec<f32> {
+    input.iter().map(|s| (*s as f32 - 128.0) / 128.0).collect()
+}
+
+/// Convert samples in the range [-1, 1] to 8-bit unsigned, clipping if necessary.
+pub fn f32_to_u8(input: &[f32]) -> Vec<u8> {
+    input
+        .iter()
+        .map(|s| (s * 128.0 + 128.0).round().clamp(0.0, 255.0) as u8)
+        .collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Zeroth order modified Bessel function of the first kind, used by the Kaiser window.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_x = x / 2.0;
+    for k in 1..50 {
+        term *= (half_x / k as f64) * (half_x / k as f64);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resample::{resample, resample_u8};
+
+    fn sine(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    fn rms(signal: &[f32]) -> f32 {
+        (signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn same_rate_is_identity() {
+        let input = vec![0, 64, 128, 255, 3];
+        assert_eq!(resample_u8(&input, 22050, 22050), input);
+    }
+
+    #[test]
+    fn output_length() {
+        let input = vec![128u8; 22050];
+        assert_eq!(resample_u8(&input, 22050, 44100).len(), 44100);
+        assert_eq!(resample_u8(&input, 22050, 48000).len(), 48000);
+        assert_eq!(resample_u8(&input, 22050, 11025).len(), 11025);
+    }
+
+    #[test]
+    fn passband_is_preserved() {
+        let input = sine(1000.0, 22050, 4410);
+        let output = resample(&input, 22050, 48000);
+        let middle = &output[1000..output.len() - 1000];
+        assert!((rms(middle) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+    }
+
+    #[test]
+    fn downsampling_removes_content_above_nyquist() {
+        // 9 kHz is above the 5512.5 Hz Nyquist frequency of the output.
+        let input = sine(9000.0, 22050, 4410);
+        let output = resample(&input, 22050, 11025);
+        let middle = &output[500..output.len() - 500];
+        assert!(rms(middle) < 0.01);
+    }
+}
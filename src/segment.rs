@@ -0,0 +1,324 @@
+use crate::{
+    phoneme_rules::PhonemeRuleSet,
+    phonemes::{convert_phonemes_with, read_phoneme, Control},
+    validate::Diagnostic,
+};
+
+// SEGMENTATION
+//
+// `convert_phonemes` holds at most 255 phonemes, and the frames that are
+// made of them at most 256, about two and a half seconds. Longer input is
+// synthesized in chunks, whose audio is put together.
+//
+// The input is split into sentences after each "." and "?". A sentence
+// that does not fit is split into clauses after "," and "-", and a clause
+// that still does not fit between words. Clauses and words are put back
+// together into as few chunks as fit, so that most of the input is
+// converted with the context of its neighbours. A phoneme keeps its stress
+// digits and length controls, and one that does not fit on its own is an
+// error. Phonemes within `MAX_LENGTH` always fit.
+//
+// Pitch and speed controls last until the next one of their kind, so the
+// last ones of a chunk are repeated at the start of the next.
+
+/// Where a chunk ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    /// After a "." or "?", or at the end of the input.
+    Sentence,
+    /// After a "," or "-".
+    Clause,
+    /// Between words, or inside a word longer than a chunk.
+    Word,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// Phonetic input, starting and ending with a space like the output of
+    /// the reciter.
+    pub phonetic: String,
+    pub boundary: Boundary,
+}
+
+/// Most phonemes in a chunk. The rules of `convert_phonemes` make up to
+/// three phonemes out of one.
+const MAX_PHONEMES: usize = 80;
+
+/// Most frames in a chunk, counting the breaths that the rule set inserts.
+/// A breath is due after `Breath::after_frames`, 232 by default, so with
+/// the default rules only chunks longer than that breathe.
+const MAX_FRAMES: usize = 255;
+
+/// Whether phonetic input is converted with `rules` and made into frames
+/// whole.
+pub fn fits(phonetic: &str, rules: &PhonemeRuleSet) -> bool {
+    fits_tokens(&tokens(phonetic), rules)
+}
+
+fn fits_tokens(tokens: &[Token], rules: &PhonemeRuleSet) -> bool {
+    if tokens.iter().map(|token| token.phonemes).sum::<usize>() > MAX_PHONEMES {
+        return false;
+    }
+    let phonetic = tokens.iter().map(|token| token.text).collect::<String>();
+    let phonemes = convert_phonemes_with(format!(" {} ", phonetic.trim()).as_bytes(), rules);
+    let frames = phonemes.phoneme_length[..phonemes.len()]
+        .iter()
+        .map(|length| *length as usize)
+        .sum::<usize>();
+    frames <= MAX_FRAMES
+}
+
+/// Split phonetic input into sentences, and sentences that do not fit with
+/// `rules`, see `fits`, into shorter chunks.
+pub fn segment(phonetic: &str, rules: &PhonemeRuleSet) -> Result<Vec<Chunk>, Diagnostic> {
+    let tokens = tokens(phonetic);
+    let mut pieces = Vec::new();
+    for sentence in split(&tokens, Boundary::Sentence) {
+        pack(sentence, Boundary::Sentence, rules, &mut pieces)?;
+    }
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let (mut pitch, mut speed) = (None, None);
+    for piece in pieces {
+        let text = piece.iter().map(|token| token.text).collect::<String>();
+        let text = text.trim();
+        let carried = pitch.into_iter().chain(speed).collect::<String>();
+        for token in piece {
+            match token.control {
+                Some(Control::Pitch(_)) => pitch = Some(token.text),
+                Some(Control::Speed(_)) => speed = Some(token.text),
+                _ => {}
+            }
+        }
+        if piece
+            .iter()
+            .all(|token| token.phonemes == 0 || token.text == " ")
+        {
+            // Spaces and controls without sound belong to the chunk before.
+            if let Some(last) = chunks.last_mut().filter(|_| !text.is_empty()) {
+                last.phonetic.push_str(text);
+                last.phonetic.push(' ');
+            }
+            continue;
+        }
+        let boundary = match text
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .chars()
+            .last()
+        {
+            Some('.' | '?') => Boundary::Sentence,
+            Some(',' | '-') => Boundary::Clause,
+            _ => Boundary::Word,
+        };
+        chunks.push(Chunk {
+            phonetic: format!(" {}{} ", carried, text),
+            boundary,
+        });
+    }
+    if let Some(last) = chunks.last_mut() {
+        last.boundary = Boundary::Sentence;
+    }
+    Ok(chunks)
+}
+
+/// A phoneme with its stress digits and length controls, a control, or any
+/// other character.
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    /// Byte offset in the input.
+    offset: usize,
+    text: &'a str,
+    /// Phonemes that `convert_phonemes` reads for the token.
+    phonemes: usize,
+    control: Option<Control>,
+}
+
+fn tokens(phonetic: &str) -> Vec<Token<'_>> {
+    let bytes = phonetic.as_bytes();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let start = idx;
+        let (mut phonemes, mut control) = (0, None);
+        // An unclosed "{" is read as any other character.
+        let close = bytes[idx..].iter().position(|c| *c == b'}');
+        if let Some(end) = close.filter(|_| bytes[idx] == b'{') {
+            idx += end + 1;
+            control = Control::parse(&bytes[start + 1..idx - 1]);
+            if let Some(Control::Pause(_)) = control {
+                phonemes = 1;
+            }
+        } else if let Some((_, length)) = read_phoneme(&bytes[idx..]) {
+            idx += length;
+            idx += bytes[idx..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            while let Some(end) = length_control(&bytes[idx..]) {
+                idx += end;
+            }
+            phonemes = 1;
+        } else {
+            idx += phonetic[idx..].chars().next().map_or(1, char::len_utf8);
+        }
+        tokens.push(Token {
+            offset: start,
+            text: &phonetic[start..idx],
+            phonemes,
+            control,
+        });
+    }
+    tokens
+}
+
+/// The end of the length control that `bytes` starts with, if any.
+fn length_control(bytes: &[u8]) -> Option<usize> {
+    if bytes.first() != Some(&b'{') {
+        return None;
+    }
+    let end = bytes.iter().position(|c| *c == b'}')? + 1;
+    let control = Control::parse(&bytes[1..end - 1]);
+    matches!(control, Some(Control::Length(_))).then_some(end)
+}
+
+fn ends(token: &Token, boundary: Boundary) -> bool {
+    let sign = token.text.as_bytes()[0];
+    match boundary {
+        Boundary::Sentence => matches!(sign, b'.' | b'?'),
+        Boundary::Clause => matches!(sign, b',' | b'-'),
+        Boundary::Word => sign == b' ',
+    }
+}
+
+/// Split after each token that ends a piece at `boundary`, or after the
+/// last of several in a row.
+fn split<'t, 'a>(tokens: &'t [Token<'a>], boundary: Boundary) -> Vec<&'t [Token<'a>]> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        let next_ends = tokens.get(idx + 1).is_some_and(|next| ends(next, boundary));
+        if ends(token, boundary) && !next_ends {
+            pieces.push(&tokens[start..=idx]);
+            start = idx + 1;
+        }
+    }
+    if start < tokens.len() {
+        pieces.push(&tokens[start..]);
+    }
+    pieces
+}
+
+/// Put the pieces of `tokens` at the boundary after `boundary` together
+/// into as few pieces as fit.
+fn pack<'t, 'a>(
+    tokens: &'t [Token<'a>],
+    boundary: Boundary,
+    rules: &PhonemeRuleSet,
+    pieces: &mut Vec<&'t [Token<'a>]>,
+) -> Result<(), Diagnostic> {
+    if fits_tokens(tokens, rules) {
+        pieces.push(tokens);
+        return Ok(());
+    }
+    let smaller = match boundary {
+        Boundary::Sentence => Boundary::Clause,
+        Boundary::Clause => Boundary::Word,
+        Boundary::Word => {
+            // A single word that is too long is cut anywhere between its
+            // tokens.
+            let mut start = 0;
+            for end in 1..=tokens.len() {
+                if end < tokens.len() && fits_tokens(&tokens[start..=end], rules) {
+                    continue;
+                }
+                let piece = &tokens[start..end];
+                if piece.len() == 1 && !fits_tokens(piece, rules) {
+                    let token = piece[0];
+                    return Err(Diagnostic::new(
+                        token.offset..token.offset + token.text.len(),
+                        "too long to synthesize on its own".to_string(),
+                    ));
+                }
+                pieces.push(piece);
+                start = end;
+            }
+            return Ok(());
+        }
+    };
+
+    let mut start = 0;
+    let mut end = 0;
+    for piece in split(tokens, smaller) {
+        let next = end + piece.len();
+        if end > start && !fits_tokens(&tokens[start..next], rules) {
+            pack(&tokens[start..end], smaller, rules, pieces)?;
+            start = end;
+        }
+        end = next;
+    }
+    pack(&tokens[start..end], smaller, rules, pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        phoneme_rules::{Breath, PhonemeRuleSet},
+        segment::{fits, segment, Boundary},
+        sequence::Phoneme,
+    };
+
+    #[test]
+    fn sentences_and_clauses() {
+        let rules = PhonemeRuleSet::default();
+        let chunks = segment("{pitch=50}/HEH4LOW. AY4 AEM SAEM? ", &rules).unwrap();
+        let phonetic = chunks
+            .iter()
+            .map(|chunk| chunk.phonetic.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            phonetic,
+            [" {pitch=50}/HEH4LOW. ", " {pitch=50}AY4 AEM SAEM? "]
+        );
+
+        let chunks = segment(&"IHT WAHZ DHAX BEH4ST AHV TAY5MZ, ".repeat(12), &rules).unwrap();
+        assert!(chunks.len() > 1);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            assert!(fits(&chunk.phonetic, &rules));
+            assert!(chunk.phonetic.starts_with(" IHT WAHZ"));
+            let boundary = if idx + 1 == chunks.len() {
+                Boundary::Sentence
+            } else {
+                Boundary::Clause
+            };
+            assert_eq!(chunk.boundary, boundary);
+        }
+    }
+
+    #[test]
+    fn unclosed_control() {
+        let chunks = segment("AY4 {pitch=50", &PhonemeRuleSet::default()).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].phonetic, " AY4 {pitch=50 ");
+    }
+
+    #[test]
+    fn long_phonemes() {
+        // A word is cut between phonemes, which keep their lengths.
+        let mut rules = PhonemeRuleSet::default();
+        let chunks = segment("AA{len=150}AA{len=150}", &rules).unwrap();
+        let phonetic = chunks
+            .iter()
+            .map(|chunk| chunk.phonetic.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(phonetic, [" AA{len=150} ", " AA{len=150} "]);
+
+        // A long breath before a phoneme leaves it no room.
+        rules.breath = Some(Breath {
+            after_frames: 100,
+            pause: Phoneme::new(31, 120, 0),
+            sound: None,
+        });
+        let error = segment(" AA AA{len=150} ", &rules).unwrap_err();
+        assert_eq!(error.span, 4..15);
+    }
+}
@@ -0,0 +1,235 @@
+use std::{fmt, ops::RangeBounds};
+
+use crate::phonemes::{read_phoneme, Phonemes, PHONEME_LENGTH_TABLE};
+
+// PHONEME SEQUENCES
+//
+// `Phonemes` holds the phoneme list the way SAM does: three arrays of 256
+// bytes, ended by a phoneme index of 255. A `PhonemeSequence` holds the
+// same list as a vector of typed entries, so that tools can build a list
+// or edit the output of `convert_phonemes` before it goes to `mk_frames`.
+//
+//     let mut sequence = PhonemeSequence::from(&convert_phonemes(b"HEHLOW "));
+//     sequence.insert(0, Phoneme::named("/H").unwrap());
+//     let phonemes = Phonemes::try_from(&sequence).unwrap();
+//
+// Pinned pitches move with their phonemes. Inline controls are not kept:
+// their lengths are already in the entries, but pitch and speed changes
+// are lost.
+
+/// Longest sequence that fits in `Phonemes`, leaving room for the 255 at
+/// the end.
+pub const MAX_LEN: usize = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Phoneme {
+    /// Index of the phoneme, as printed by `print_phonemes`.
+    pub phoneme: u8,
+    /// Length in frames.
+    pub length: u8,
+    /// Stress, 0 for none and 1 (most) to 8.
+    pub stress: u8,
+    /// Pinned pitch, see `Phonemes::pin_pitch`.
+    pub pitch: Option<u8>,
+}
+
+impl Phoneme {
+    pub fn new(phoneme: u8, length: u8, stress: u8) -> Self {
+        Self {
+            phoneme,
+            length,
+            stress,
+            pitch: None,
+        }
+    }
+
+    /// The phoneme with a name from the phonetic notation, e.g. "AA" or
+    /// "/H", unstressed and with its usual unstressed length.
+    pub fn named(name: &str) -> Option<Self> {
+        match read_phoneme(name.as_bytes())? {
+            (phoneme, length) if length == name.len() => {
+                let frames = PHONEME_LENGTH_TABLE.get(phoneme as usize).copied();
+                Some(Self::new(phoneme, frames.unwrap_or(0), 0))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhonemeSequence {
+    entries: Vec<Phoneme>,
+}
+
+impl PhonemeSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Phoneme> {
+        self.entries.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Phoneme> {
+        self.entries.get_mut(idx)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Phoneme> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Phoneme> {
+        self.entries.iter_mut()
+    }
+
+    pub fn push(&mut self, phoneme: Phoneme) {
+        self.entries.push(phoneme);
+    }
+
+    /// Insert before the entry at `idx`. Panics if `idx > len`.
+    pub fn insert(&mut self, idx: usize, phoneme: Phoneme) {
+        self.entries.insert(idx, phoneme);
+    }
+
+    /// Remove and return the entry at `idx`. Panics if `idx >= len`.
+    pub fn remove(&mut self, idx: usize) -> Phoneme {
+        self.entries.remove(idx)
+    }
+
+    /// Replace the entry at `idx`, returning the old one. Panics if
+    /// `idx >= len`.
+    pub fn replace(&mut self, idx: usize, phoneme: Phoneme) -> Phoneme {
+        std::mem::replace(&mut self.entries[idx], phoneme)
+    }
+
+    /// Replace a range of entries with other entries, returning the ones
+    /// removed.
+    pub fn splice(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        phonemes: impl IntoIterator<Item = Phoneme>,
+    ) -> Vec<Phoneme> {
+        self.entries.splice(range, phonemes).collect()
+    }
+}
+
+impl FromIterator<Phoneme> for PhonemeSequence {
+    fn from_iter<I: IntoIterator<Item = Phoneme>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for PhonemeSequence {
+    type Item = Phoneme;
+    type IntoIter = std::vec::IntoIter<Phoneme>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PhonemeSequence {
+    type Item = &'a Phoneme;
+    type IntoIter = std::slice::Iter<'a, Phoneme>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl From<&Phonemes> for PhonemeSequence {
+    fn from(phonemes: &Phonemes) -> Self {
+        (0..phonemes.len())
+            .map(|idx| Phoneme {
+                phoneme: phonemes.phoneme_index[idx],
+                length: phonemes.phoneme_length[idx],
+                stress: phonemes.stress[idx],
+                pitch: phonemes
+                    .pitch_pins
+                    .iter()
+                    .find(|(i, _)| *i == idx)
+                    .map(|(_, pitch)| *pitch),
+            })
+            .collect()
+    }
+}
+
+/// The sequence has more than `MAX_LEN` entries.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TooLong(pub usize);
+
+impl fmt::Display for TooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} phonemes is more than the limit of {}",
+            self.0, MAX_LEN
+        )
+    }
+}
+
+impl std::error::Error for TooLong {}
+
+impl TryFrom<&PhonemeSequence> for Phonemes {
+    type Error = TooLong;
+
+    fn try_from(sequence: &PhonemeSequence) -> Result<Self, TooLong> {
+        if sequence.len() > MAX_LEN {
+            return Err(TooLong(sequence.len()));
+        }
+        let mut phonemes = Phonemes::default();
+        for (idx, entry) in sequence.iter().enumerate() {
+            phonemes.phoneme_index[idx] = entry.phoneme;
+            phonemes.phoneme_length[idx] = entry.length;
+            phonemes.stress[idx] = entry.stress;
+            if let Some(pitch) = entry.pitch {
+                phonemes.pitch_pins.push((idx, pitch));
+            }
+        }
+        phonemes.phoneme_index[sequence.len()] = 255;
+        Ok(phonemes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        phonemes::Phonemes,
+        sequence::{Phoneme, PhonemeSequence, TooLong},
+    };
+
+    #[test]
+    fn edit_and_convert() {
+        let mut sequence = ["M", "AA", "S"]
+            .iter()
+            .map(|name| Phoneme::named(name).unwrap())
+            .collect::<PhonemeSequence>();
+        assert_eq!(sequence.get(1), Some(&Phoneme::new(9, 11, 0)));
+        assert_eq!(Phoneme::named("AAX"), None);
+
+        sequence.replace(2, Phoneme::named("N").unwrap());
+        sequence.insert(0, Phoneme::named("/H").unwrap());
+        sequence.remove(1);
+        sequence.get_mut(1).unwrap().pitch = Some(40);
+        let removed = sequence.splice(2.., [Phoneme::new(0, 4, 0)]);
+        assert_eq!(removed.len(), 1);
+
+        let phonemes = Phonemes::try_from(&sequence).unwrap();
+        assert_eq!(phonemes.phoneme_index[..4], [36, 9, 0, 255]);
+        assert_eq!(phonemes.pitch_pins, [(1, 40)]);
+        assert_eq!(PhonemeSequence::from(&phonemes), sequence);
+
+        let long = std::iter::repeat_n(Phoneme::new(0, 1, 0), 256).collect::<PhonemeSequence>();
+        assert_eq!(Phonemes::try_from(&long).err(), Some(TooLong(256)));
+    }
+}
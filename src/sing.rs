@@ -0,0 +1,236 @@
+use crate::{
+    frames::{breath_groups, mk_frames, FormantTables, FramesTables, SAMPLE_RATE, TICK_SAMPLES},
+    midi::{MidiError, Note},
+    phoneme_rules::PhonemeRuleSet,
+    phonemes::{convert_phonemes, is_vowel, Phonemes},
+    pitch::midi_note_to_pitch,
+    segment::fits,
+    validate::validate_phonetic,
+    Params,
+};
+
+// SINGING
+//
+// Each lyric is sung as one syllable, written in SAM's phonetic notation,
+// e.g. "LAA" or "/HEH". A note without a lyric that follows straight on
+// from the previous one is a melisma: the previous syllable is held and
+// moves to the new pitch. Other notes without a lyric are not sung.
+//
+// A syllable is converted to frames on its own. The vowels are stretched
+// or shrunk to make the syllable last as long as its notes, and the pitch
+// of every frame is set from the note sounding at that time, replacing the
+// stress and F1 pitch contour. A lyric that is not phonetic input, or does
+// not fit, see `segment::fits`, is a `MidiError::InvalidLyric`.
+
+/// Most frames a syllable may use, leaving room for the transitions at the
+/// end of the 256 frame tables.
+const MAX_FRAMES: usize = 240;
+
+/// Longest gap between notes that still continues a syllable, in seconds.
+const LEGATO_GAP: f64 = 0.05;
+
+/// A lyric and the notes it is sung on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Syllable {
+    pub phonetic: String,
+    pub notes: Vec<Note>,
+}
+
+impl Syllable {
+    pub fn start(&self) -> f64 {
+        self.notes[0].start
+    }
+
+    pub fn end(&self) -> f64 {
+        self.notes[self.notes.len() - 1].end
+    }
+}
+
+/// Group notes into syllables.
+pub fn syllables(notes: &[Note]) -> Vec<Syllable> {
+    let mut syllables: Vec<Syllable> = Vec::new();
+    for note in notes {
+        let phonetic = note.lyric.as_deref().map(clean_lyric).unwrap_or_default();
+        if !phonetic.is_empty() {
+            syllables.push(Syllable {
+                phonetic,
+                notes: vec![note.clone()],
+            });
+        } else if let Some(syllable) = syllables.last_mut() {
+            if note.start - syllable.end() <= LEGATO_GAP {
+                syllable.notes.push(note.clone());
+            }
+        }
+    }
+    syllables
+}
+
+/// Remove the syllable hyphens and karaoke line breaks found in lyric
+/// events.
+fn clean_lyric(lyric: &str) -> String {
+    lyric
+        .trim_matches(|c: char| c.is_whitespace() || c == '-' || c == '\\')
+        .to_ascii_uppercase()
+}
+
+pub(crate) fn syllable_frames(
+    params: &Params,
+    syllable: &Syllable,
+) -> Result<Vec<FramesTables>, MidiError> {
+    if !validate_phonetic(&syllable.phonetic).is_empty()
+        || !fits(&syllable.phonetic, &PhonemeRuleSet::default())
+    {
+        return Err(MidiError::InvalidLyric(syllable.phonetic.clone()));
+    }
+
+    let tick_seconds = TICK_SAMPLES / SAMPLE_RATE as f64;
+
+    let input = format!("{} ", syllable.phonetic);
+    let mut phonemes = convert_phonemes(input.as_bytes());
+    let duration_ticks = (syllable.end() - syllable.start()) / tick_seconds;
+    let vowel_speed = fit_vowels(&mut phonemes, duration_ticks, params.speed);
+
+    let formants = FormantTables::from_params(params);
+    let mut groups = mk_frames(params, &phonemes, &formants);
+
+    let mut time = syllable.start();
+    for (phonemes, frames) in breath_groups(&phonemes).iter().zip(&mut groups) {
+        set_notes(syllable, phonemes, frames, vowel_speed, &mut time);
+    }
+
+    Ok(groups)
+}
+
+/// Set the pitches of the notes in the frames of a breath group, and the
+/// speed of its vowels. `time` is when the group starts, and is moved to
+/// its end.
+fn set_notes(
+    syllable: &Syllable,
+    phonemes: &Phonemes,
+    frames: &mut FramesTables,
+    vowel_speed: u8,
+    time: &mut f64,
+) {
+    let tick_seconds = TICK_SAMPLES / SAMPLE_RATE as f64;
+    let mut frame_idx = 0;
+    for idx in 0..255 {
+        let phoneme = phonemes.phoneme_index[idx];
+        if phoneme == 255 {
+            break;
+        }
+        for _ in 0..phonemes.phoneme_length[idx] {
+            if is_vowel(phoneme) {
+                frames.speeds[frame_idx] = vowel_speed;
+            }
+            let note = syllable
+                .notes
+                .iter()
+                .rev()
+                .find(|note| note.start <= *time)
+                .unwrap_or(&syllable.notes[0]);
+            frames.pitches[frame_idx] = midi_note_to_pitch(note.key);
+
+            *time += frames.speeds[frame_idx] as f64 * tick_seconds;
+            frame_idx += 1;
+        }
+    }
+}
+
+/// Set the vowel lengths so that the phonemes last `duration_ticks`, with
+/// the consonants at `speed`. Returns the speed for the vowel frames, which
+/// is slower than `speed` when a long note doesn't fit in `MAX_FRAMES`.
+fn fit_vowels(phonemes: &mut Phonemes, duration_ticks: f64, speed: u8) -> u8 {
+    let mut vowels = Vec::new();
+    let mut consonant_frames = 0;
+    for idx in 0..255 {
+        let phoneme = phonemes.phoneme_index[idx];
+        if phoneme == 255 {
+            break;
+        }
+        if is_vowel(phoneme) {
+            vowels.push(idx);
+        } else {
+            consonant_frames += phonemes.phoneme_length[idx] as usize;
+        }
+    }
+    if vowels.is_empty() {
+        return speed;
+    }
+
+    let vowel_ticks = (duration_ticks - (consonant_frames * speed as usize) as f64).max(0.0);
+    let max_frames = MAX_FRAMES
+        .saturating_sub(consonant_frames)
+        .max(vowels.len());
+    let target = ((vowel_ticks / speed as f64).round() as usize).clamp(vowels.len(), max_frames);
+
+    // Share the frames out in proportion to the original lengths.
+    let weights = vowels
+        .iter()
+        .map(|idx| phonemes.phoneme_length[*idx].max(1) as usize)
+        .collect::<Vec<_>>();
+    let total_weight = weights.iter().sum::<usize>();
+    let mut weight_before = 0;
+    let mut vowel_frames = 0;
+    for (idx, weight) in vowels.iter().zip(weights) {
+        let start = (weight_before * target + total_weight / 2) / total_weight;
+        weight_before += weight;
+        let end = (weight_before * target + total_weight / 2) / total_weight;
+        let length = (end - start).clamp(1, 255);
+        phonemes.phoneme_length[*idx] = length as u8;
+        vowel_frames += length;
+    }
+
+    (vowel_ticks / vowel_frames as f64)
+        .round()
+        .clamp(1.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        midi::Note,
+        phonemes::Phonemes,
+        sing::{fit_vowels, syllables},
+    };
+
+    fn note(start: f64, end: f64, lyric: Option<&str>) -> Note {
+        Note {
+            start,
+            end,
+            key: 60,
+            lyric: lyric.map(String::from),
+        }
+    }
+
+    #[test]
+    fn melisma_and_rests() {
+        let notes = [
+            note(0.0, 1.0, Some("lo-")),
+            note(1.0, 1.5, None),
+            note(2.0, 2.5, None),
+            note(2.5, 3.0, Some("\\-")),
+            note(3.0, 4.0, Some("VE")),
+        ];
+        let syllables = syllables(&notes);
+        assert_eq!(syllables.len(), 2);
+        assert_eq!(syllables[0].phonetic, "LO");
+        assert_eq!((syllables[0].start(), syllables[0].end()), (0.0, 1.5));
+        assert_eq!(syllables[1].phonetic, "VE");
+    }
+
+    #[test]
+    fn vowels_fill_note() {
+        // M AA
+        let mut phonemes = Phonemes::default();
+        phonemes.phoneme_index[..3].copy_from_slice(&[27, 9, 255]);
+        phonemes.phoneme_length[..2].copy_from_slice(&[6, 10]);
+
+        assert_eq!(fit_vowels(&mut phonemes, 40.0 * 72.0, 72), 72);
+        assert_eq!(phonemes.phoneme_length[..2], [6, 34]);
+
+        // Too long for the frame tables, so the vowel frames are slower.
+        let speed = fit_vowels(&mut phonemes, 1000.0 * 72.0, 72);
+        assert_eq!(phonemes.phoneme_length[..2], [6, 234]);
+        assert_eq!(speed, ((1000.0 - 6.0) * 72.0 / 234.0f64).round() as u8);
+    }
+}
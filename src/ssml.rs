@@ -0,0 +1,623 @@
+use std::fmt;
+
+use crate::{
+    frames::{SAMPLE_RATE, TICK_SAMPLES},
+    phonemes::{is_vowel, read_phoneme},
+    pitch::{hz_to_pitch, pitch_to_hz},
+    reciter::{ReadingMode, Reciter},
+    validate::validate_phonetic,
+    Params,
+};
+
+// SSML
+//
+// Reads a subset of the Speech Synthesis Markup Language and maps it onto
+// SAM's model:
+//
+//     <speak>, <s>, <p>        Sentences and paragraphs end with a period.
+//     <break>                  Pause phonemes, see `pauses`.
+//     <prosody rate pitch>     Speed and pitch for the enclosed text.
+//     <emphasis level>         Stress digits, see `emphasize`.
+//     <say-as interpret-as="characters">
+//                              Spelled out letter by letter, and with
+//                              "digits" or "telephone", numbers are read
+//                              digit by digit, see `ReadingMode`.
+//     <phoneme alphabet="sam" ph="...">
+//                              SAM phonetic input, checked with
+//                              `validate_phonetic` and used verbatim.
+//
+// Other elements are skipped, but the text inside them is read. Text is
+// converted to phonemes by the reciter. An empty element, e.g. <prosody/>,
+// encloses no text, so it changes nothing after it.
+//
+// The result is a list of segments, one for every change of `Params`,
+// since the voice parameters are fixed for each call to `mk_frames`.
+
+/// Phonetic input and the voice to render it with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub params: Params,
+    pub phonetic: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SsmlError {
+    /// Byte offset in the document.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SsmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for SsmlError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Emphasis {
+    Strong,
+    Moderate,
+    Reduced,
+    None,
+}
+
+#[derive(Clone)]
+struct State {
+    params: Params,
+    emphasis: Option<Emphasis>,
+    mode: ReadingMode,
+    /// Inside a <phoneme> whose pronunciation replaces the text.
+    skip_text: bool,
+}
+
+/// Convert an SSML document to segments. Prosody is relative to `params`.
+pub fn parse_ssml(ssml: &str, params: &Params) -> Result<Vec<Segment>, SsmlError> {
+    parse_ssml_with(ssml, params, &Reciter::default())
+}
+
+/// Like `parse_ssml`, with the text converted by `reciter`.
+pub fn parse_ssml_with(
+    ssml: &str,
+    params: &Params,
+    reciter: &Reciter,
+) -> Result<Vec<Segment>, SsmlError> {
+    let mut segments = Segments::default();
+    let mut state = State {
+        params: *params,
+        emphasis: None,
+        mode: ReadingMode::Words,
+        skip_text: false,
+    };
+    let mut open = Vec::new();
+
+    for (offset, token) in tokens(ssml)? {
+        let error = |message: String| SsmlError { offset, message };
+        match token {
+            Token::Text(text) => {
+                if state.skip_text {
+                    continue;
+                }
+                if text.trim().is_empty() {
+                    segments.space();
+                    continue;
+                }
+                let phonetic = reciter.read(&text, state.mode);
+                let phonetic = match state.emphasis {
+                    Some(level) => emphasize(&phonetic, level),
+                    None => phonetic,
+                };
+                segments.push(&state.params, &phonetic);
+            }
+            Token::Start {
+                name,
+                attributes,
+                empty,
+            } => {
+                let attribute = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, v)| v.as_str())
+                };
+                // The state inside the element.
+                let mut inner = state.clone();
+                match name {
+                    "break" => {
+                        let ms = match (attribute("time"), attribute("strength")) {
+                            (Some(time), _) => parse_time(time).map_err(error)?,
+                            (None, Some(strength)) => strength_ms(strength).map_err(error)?,
+                            (None, None) => strength_ms("medium").unwrap(),
+                        };
+                        segments.push(&state.params, &pauses(ms, state.params.speed));
+                    }
+                    "prosody" => {
+                        if let Some(rate) = attribute("rate") {
+                            inner.params.speed = parse_rate(rate, state.params.speed, params.speed)
+                                .map_err(error)?;
+                        }
+                        if let Some(pitch) = attribute("pitch") {
+                            inner.params.pitch =
+                                parse_pitch(pitch, state.params.pitch, params.pitch)
+                                    .map_err(error)?;
+                        }
+                    }
+                    "emphasis" => {
+                        inner.emphasis = Some(match attribute("level").unwrap_or("moderate") {
+                            "strong" => Emphasis::Strong,
+                            "moderate" => Emphasis::Moderate,
+                            "reduced" => Emphasis::Reduced,
+                            "none" => Emphasis::None,
+                            level => return Err(error(format!("unknown emphasis {}", level))),
+                        });
+                    }
+                    "say-as" => {
+                        let interpret_as = attribute("interpret-as").unwrap_or_default();
+                        inner.mode = match interpret_as {
+                            "characters" | "spell-out" => ReadingMode::Spell,
+                            "digits" | "telephone" => ReadingMode::Digits,
+                            _ => ReadingMode::Words,
+                        };
+                    }
+                    "phoneme" => {
+                        if let (Some("sam"), Some(ph)) = (attribute("alphabet"), attribute("ph")) {
+                            let ph = ph.to_uppercase();
+                            if let Some(diagnostic) = validate_phonetic(&ph).first() {
+                                return Err(error(format!(
+                                    "invalid phoneme {}: {}",
+                                    ph, diagnostic.message
+                                )));
+                            }
+                            segments.push(&state.params, &format!(" {} ", ph));
+                            inner.skip_text = true;
+                        }
+                    }
+                    _ => {}
+                }
+
+                if !empty {
+                    open.push((name, std::mem::replace(&mut state, inner)));
+                }
+            }
+            Token::End(name) => {
+                let Some((open_name, open_state)) = open.pop() else {
+                    return Err(error(format!("unexpected </{}>", name)));
+                };
+                if open_name != name {
+                    return Err(error(format!(
+                        "expected </{}>, found </{}>",
+                        open_name, name
+                    )));
+                }
+                state = open_state;
+                if matches!(name, "s" | "p") {
+                    segments.end_sentence();
+                }
+            }
+        }
+    }
+
+    if let Some((name, _)) = open.pop() {
+        return Err(SsmlError {
+            offset: ssml.len(),
+            message: format!("<{}> is not closed", name),
+        });
+    }
+
+    segments
+        .list
+        .retain(|segment| !segment.phonetic.trim().is_empty());
+    Ok(segments.list)
+}
+
+#[derive(Default)]
+struct Segments {
+    list: Vec<Segment>,
+}
+
+impl Segments {
+    fn push(&mut self, params: &Params, phonetic: &str) {
+        match self.list.last_mut() {
+            Some(last) if last.params == *params => last.phonetic.push_str(phonetic),
+            _ => self.list.push(Segment {
+                params: *params,
+                phonetic: phonetic.to_string(),
+            }),
+        }
+    }
+
+    /// Separate words without starting a new segment.
+    fn space(&mut self) {
+        if let Some(last) = self.list.last_mut() {
+            last.phonetic.push(' ');
+        }
+    }
+
+    /// End the last segment with a period, unless it already ends a
+    /// sentence. The period goes in the same segment as the words before
+    /// it, which it lengthens and inflects.
+    fn end_sentence(&mut self) {
+        if let Some(last) = self.list.last_mut() {
+            let trimmed = last.phonetic.trim_end();
+            if !trimmed.is_empty() && !trimmed.ends_with(['.', '?']) {
+                last.phonetic.truncate(trimmed.len());
+                last.phonetic.push_str(". ");
+            }
+        }
+    }
+}
+
+/// Pause phonemes lasting about `ms` milliseconds. A comma pauses for 18
+/// frames and a dash for 8.
+fn pauses(ms: f64, speed: u8) -> String {
+    let frame_ms = speed as f64 * TICK_SAMPLES / SAMPLE_RATE as f64 * 1000.0;
+    let frames = (ms / frame_ms).round() as usize;
+
+    let mut phonetic = String::from(" ");
+    for _ in 0..frames / 18 {
+        phonetic.push_str(", ");
+    }
+    if frames % 18 >= 4 {
+        phonetic.push_str("- ");
+    }
+    phonetic
+}
+
+/// Change the stress digits of phonetic input:
+///
+/// - strong and moderate emphasis use stress 2 and 3, which raise the pitch
+///   more than the reciter's usual 4 and 5, and also stress the first vowel
+///   of words that have no stress,
+/// - reduced emphasis lowers stress to 6,
+/// - no emphasis removes the stress.
+fn emphasize(phonetic: &str, level: Emphasis) -> String {
+    let stress = match level {
+        Emphasis::Strong => Some(b'2'),
+        Emphasis::Moderate => Some(b'3'),
+        Emphasis::Reduced => Some(b'6'),
+        Emphasis::None => None,
+    };
+    let stress_unstressed = matches!(level, Emphasis::Strong | Emphasis::Moderate);
+
+    let mut output = Vec::new();
+    for (word_idx, word) in phonetic.as_bytes().split(|c| *c == b' ').enumerate() {
+        if word_idx > 0 {
+            output.push(b' ');
+        }
+
+        let mut stressed = false;
+        let mut first_vowel_end = None;
+        let mut idx = 0;
+        while idx < word.len() {
+            if (b'1'..=b'8').contains(&word[idx]) {
+                stressed = true;
+                output.extend(stress);
+                idx += 1;
+                continue;
+            }
+            let length = match read_phoneme(&word[idx..]) {
+                Some((phoneme, length)) => {
+                    if is_vowel(phoneme) && first_vowel_end.is_none() {
+                        first_vowel_end = Some(output.len() + length);
+                    }
+                    length
+                }
+                None => 1,
+            };
+            output.extend(&word[idx..idx + length]);
+            idx += length;
+        }
+
+        if let (false, true, Some(end)) = (stressed, stress_unstressed, first_vowel_end) {
+            output.insert(end, stress.unwrap());
+        }
+    }
+
+    String::from_utf8(output).unwrap()
+}
+
+/// Milliseconds in "500ms" or "1.5s".
+fn parse_time(time: &str) -> Result<f64, String> {
+    let time = time.trim();
+    let (number, scale) = match time.strip_suffix("ms") {
+        Some(number) => (number, 1.0),
+        None => (time.strip_suffix('s').unwrap_or(time), 1000.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(value) if value >= 0.0 => Ok(value * scale),
+        _ => Err(format!("invalid break time {}", time)),
+    }
+}
+
+fn strength_ms(strength: &str) -> Result<f64, String> {
+    match strength {
+        "none" => Ok(0.0),
+        "x-weak" => Ok(100.0),
+        "weak" => Ok(250.0),
+        "medium" => Ok(500.0),
+        "strong" => Ok(750.0),
+        "x-strong" => Ok(1000.0),
+        _ => Err(format!("invalid break strength {}", strength)),
+    }
+}
+
+/// A relative change in percent, "+10%" or "-5%".
+fn parse_relative(value: &str, suffix: &str) -> Option<f64> {
+    if !value.starts_with(['+', '-']) {
+        return None;
+    }
+    value.strip_suffix(suffix)?.parse::<f64>().ok()
+}
+
+/// The speed for a prosody rate. Keywords and plain percentages are
+/// relative to the speed of the document, signed percentages to the
+/// enclosing speed. A higher rate is a lower speed value.
+fn parse_rate(rate: &str, current: u8, base: u8) -> Result<u8, String> {
+    let rate = rate.trim();
+    let (speed, factor) = match rate {
+        "x-slow" => (base, 0.5),
+        "slow" => (base, 0.75),
+        "medium" | "default" => (base, 1.0),
+        "fast" => (base, 1.5),
+        "x-fast" => (base, 2.0),
+        _ => match parse_relative(rate, "%") {
+            Some(percent) => (current, 1.0 + percent / 100.0),
+            None => match rate.strip_suffix('%').unwrap_or(rate).parse::<f64>() {
+                Ok(value) if rate.ends_with('%') => (base, value / 100.0),
+                Ok(value) => (base, value),
+                Err(_) => return Err(format!("invalid prosody rate {}", rate)),
+            },
+        },
+    };
+    if factor <= 0.0 {
+        return Err(format!("invalid prosody rate {}", rate));
+    }
+    Ok((speed as f64 / factor).round().clamp(1.0, 255.0) as u8)
+}
+
+/// The pitch value for a prosody pitch. Keywords are relative to the pitch
+/// of the document, "+10%", "+2st" and "+20Hz" to the enclosing pitch, and
+/// "200Hz" is absolute.
+fn parse_pitch(pitch: &str, current: u8, base: u8) -> Result<u8, String> {
+    let pitch = pitch.trim();
+    let semitones = |pitch: u8, semitones: f64| pitch_to_hz(pitch) * 2f64.powf(semitones / 12.0);
+    let hz = match pitch {
+        "x-low" => semitones(base, -6.0),
+        "low" => semitones(base, -3.0),
+        "medium" | "default" => pitch_to_hz(base),
+        "high" => semitones(base, 3.0),
+        "x-high" => semitones(base, 6.0),
+        _ => {
+            if let Some(percent) = parse_relative(pitch, "%") {
+                pitch_to_hz(current) * (1.0 + percent / 100.0)
+            } else if let Some(st) = parse_relative(pitch, "st") {
+                semitones(current, st)
+            } else if let Some(hz) = parse_relative(pitch, "Hz") {
+                pitch_to_hz(current) + hz
+            } else {
+                match pitch.strip_suffix("Hz").map(str::parse::<f64>) {
+                    Some(Ok(hz)) => hz,
+                    _ => return Err(format!("invalid prosody pitch {}", pitch)),
+                }
+            }
+        }
+    };
+    if hz <= 0.0 {
+        return Err(format!("invalid prosody pitch {}", pitch));
+    }
+    Ok(hz_to_pitch(hz))
+}
+
+// XML
+//
+// Just enough XML for SSML documents: elements, attributes, text, the
+// predefined and numeric character references, and CDATA sections.
+// Comments, processing instructions and the DOCTYPE are skipped.
+
+enum Token<'a> {
+    Text(String),
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End(&'a str),
+}
+
+fn tokens(xml: &str) -> Result<Vec<(usize, Token<'_>)>, SsmlError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < xml.len() {
+        let rest = &xml[pos..];
+        let error = |message: &str| SsmlError {
+            offset: pos,
+            message: message.to_string(),
+        };
+        let skip_to = |end: &str| {
+            rest.find(end)
+                .map(|idx| pos + idx + end.len())
+                .ok_or_else(|| error(&format!("missing {}", end)))
+        };
+
+        if rest.starts_with("<!--") {
+            pos = skip_to("-->")?;
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = skip_to("]]>")?;
+            let text = &cdata[..end - pos - "<![CDATA[]]>".len()];
+            tokens.push((pos, Token::Text(text.to_string())));
+            pos = end;
+        } else if rest.starts_with("<?") {
+            pos = skip_to("?>")?;
+        } else if rest.starts_with("<!") {
+            pos = skip_to(">")?;
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = tag.find('>').ok_or_else(|| error("missing >"))?;
+            tokens.push((pos, Token::End(tag[..end].trim())));
+            pos += 2 + end + 1;
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let (token, length) = start_tag(tag).map_err(|message| error(&message))?;
+            tokens.push((pos, token));
+            pos += 1 + length;
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = decode(&rest[..end]).map_err(|message| error(&message))?;
+            tokens.push((pos, Token::Text(text)));
+            pos += end;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a start tag after the '<', returning the token and its length.
+fn start_tag(tag: &str) -> Result<(Token<'_>, usize), String> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=';
+    let name_end = tag.find(is_name_end).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        return Err("missing element name".to_string());
+    }
+
+    let mut attributes = Vec::new();
+    let mut pos = name_end;
+    loop {
+        let rest = tag[pos..].trim_start();
+        pos = tag.len() - rest.len();
+        if rest.starts_with("/>") {
+            let token = Token::Start {
+                name,
+                attributes,
+                empty: true,
+            };
+            return Ok((token, pos + 2));
+        }
+        if rest.starts_with('>') {
+            let token = Token::Start {
+                name,
+                attributes,
+                empty: false,
+            };
+            return Ok((token, pos + 1));
+        }
+
+        let key_end = rest.find(is_name_end).unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        let value = rest[key_end..].trim_start();
+        let Some(value) = value.strip_prefix('=').map(str::trim_start) else {
+            return Err(format!("missing value for attribute {} of <{}>", key, name));
+        };
+        let quote = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(format!("attribute {} of <{}> is not quoted", key, name)),
+        };
+        let Some(value_end) = value[1..].find(quote) else {
+            return Err(format!("attribute {} of <{}> is not closed", key, name));
+        };
+        attributes.push((key, decode(&value[1..1 + value_end])?));
+        pos = tag.len() - value.len() + 1 + value_end + 1;
+    }
+}
+
+/// Replace character references.
+fn decode(text: &str) -> Result<String, String> {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| "unterminated character reference".to_string())?;
+        let reference = &rest[start + 1..start + end];
+        let c = match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match reference.strip_prefix('#') {
+                Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16)
+                    .ok()
+                    .and_then(char::from_u32),
+                Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                None => None,
+            },
+        };
+        output.push(c.ok_or_else(|| format!("unknown character reference &{};", reference))?);
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ssml::{emphasize, parse_ssml, Emphasis},
+        Params,
+    };
+
+    #[test]
+    fn segments() {
+        let ssml = r#"<?xml version="1.0"?>
+            <speak>
+              <s>Hi <break time="400ms"/> <prosody rate="50%" pitch="+12st">SAM</prosody></s>
+              <say-as interpret-as="characters">ab</say-as>
+              <say-as interpret-as="telephone">71</say-as>
+              <phoneme alphabet="sam" ph="/HEHLOW">hello &amp; goodbye</phoneme>
+            </speak>"#;
+        let params = Params::default();
+        let segments = parse_ssml(ssml, &params).unwrap();
+
+        assert_eq!(segments.len(), 3);
+        assert_eq!(
+            segments[0].phonetic.split_whitespace().collect::<Vec<_>>(),
+            ["/HAY", ",", ","]
+        );
+        assert_eq!(segments[1].params.speed, 144);
+        assert_eq!(segments[1].params.pitch, 32);
+        assert!(segments[1].phonetic.trim_end().ends_with('.'));
+        assert_eq!(
+            segments[2].phonetic.split_whitespace().collect::<Vec<_>>(),
+            ["EY4", "-", "BIY4", "SEH4VUN", "-", "WAH4N", "/HEHLOW"]
+        );
+
+        let error = parse_ssml("<speak><s>hi</speak>", &params).unwrap_err();
+        assert_eq!(error.offset, 12);
+        assert!(parse_ssml("<break time='soon'/>", &params).is_err());
+        assert!(parse_ssml("<phoneme alphabet='sam' ph='XQZ'/>", &params).is_err());
+    }
+
+    #[test]
+    fn empty_elements() {
+        let params = Params::default();
+        let phonetic = |ssml: &str| {
+            let segments = parse_ssml(ssml, &params).unwrap();
+            assert_eq!(segments.len(), 1);
+            assert_eq!(segments[0].params, params);
+            segments[0]
+                .phonetic
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            phonetic(r#"hello <phoneme alphabet="sam" ph="AY4"/> world"#),
+            "/HEHLOW AY4 WERLD"
+        );
+        assert_eq!(
+            phonetic(r#"<prosody pitch="+12st"/>hello <emphasis/>world"#),
+            "/HEHLOW WERLD"
+        );
+        assert_eq!(
+            phonetic(r#"<say-as interpret-as="characters"/>ab"#),
+            phonetic("ab")
+        );
+    }
+
+    #[test]
+    fn stress() {
+        assert_eq!(emphasize(" DHAX KAE4T", Emphasis::Strong), " DHAX2 KAE2T");
+        assert_eq!(emphasize("/HEH5LOW", Emphasis::Reduced), "/HEH6LOW");
+        assert_eq!(emphasize("/HEH5LOW", Emphasis::None), "/HEHLOW");
+    }
+}
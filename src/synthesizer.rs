@@ -0,0 +1,291 @@
+use crate::{
+    automation::Automation,
+    frames::{mk_frames_automated, mk_wav, FramesTables, SAMPLE_RATE},
+    language::Language,
+    midi::{read_notes, MidiError, Note},
+    phoneme_rules::PhonemeRuleSet,
+    phonemes::{convert_phonemes_with, Phonemes},
+    reciter::Reciter,
+    remaster,
+    resample::{f32_to_u8, resample_u8},
+    segment::{segment, Boundary},
+    sing::{syllable_frames, syllables},
+    ssml::{parse_ssml_with, SsmlError},
+    validate::{validate_phonetic, Diagnostic},
+    Params,
+};
+
+/// Backend used to turn frames into audio.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Renderer {
+    /// The original 8-bit renderer, `mk_wav`.
+    #[default]
+    Classic,
+    /// The floating-point formant renderer in `remaster`, which renders
+    /// natively at the output sample rate.
+    Float,
+}
+
+/// Level of the unsigned 8-bit output when nothing is playing.
+const SILENCE: u8 = 128;
+
+/// Runs the whole pipeline, from phonetic input to 8-bit unsigned PCM.
+pub struct Synthesizer {
+    pub params: Params,
+    /// Sample rate of the returned buffer. The classic renderer works at
+    /// `SAMPLE_RATE` and any other rate is produced by band-limited resampling.
+    pub output_sample_rate: u32,
+    pub renderer: Renderer,
+    /// Converts the text of `synthesize_text` and `synthesize_ssml`.
+    pub reciter: Reciter,
+    /// Silence between sentences, in milliseconds, on top of the pause of
+    /// the period.
+    pub sentence_pause_ms: u32,
+    /// The rules `convert_phonemes` rewrites phonemes and their lengths
+    /// with.
+    pub phoneme_rules: PhonemeRuleSet,
+}
+
+impl Default for Synthesizer {
+    fn default() -> Self {
+        Self::new(Params::default())
+    }
+}
+
+impl Synthesizer {
+    pub fn new(params: Params) -> Self {
+        Self {
+            params,
+            output_sample_rate: SAMPLE_RATE,
+            renderer: Renderer::default(),
+            reciter: Reciter::default(),
+            sentence_pause_ms: 200,
+            phoneme_rules: PhonemeRuleSet::default(),
+        }
+    }
+
+    /// Read text in a language, replacing the reciter's rules and
+    /// normalizer with the built-in ones for it. The lexicon is kept.
+    pub fn set_language(&mut self, language: Language) {
+        let Reciter {
+            rules, normalizer, ..
+        } = Reciter::for_language(language);
+        self.reciter.rules = rules;
+        self.reciter.normalizer = normalizer;
+    }
+
+    /// Synthesize text, converted to phonemes by the reciter. Text of any
+    /// length is read, see `synthesize_phonetic`.
+    pub fn synthesize_text(&self, text: &str) -> Vec<u8> {
+        let phonetic = format!("{} ", self.reciter.text_to_phonemes(text));
+        self.chunked(&self.params, &phonetic)
+            .expect("the breath of the phoneme rules is too long")
+    }
+
+    /// Synthesize an SSML document, see `ssml`. Each segment is rendered
+    /// with its own voice parameters.
+    pub fn synthesize_ssml(&self, ssml: &str) -> Result<Vec<u8>, SsmlError> {
+        let segments = parse_ssml_with(ssml, &self.params, &self.reciter)?;
+        Ok(segments
+            .iter()
+            .flat_map(|segment| {
+                self.chunked(&segment.params, &segment.phonetic)
+                    .expect("the breath of the phoneme rules is too long")
+            })
+            .collect())
+    }
+
+    /// Synthesize phonetic input of any length. It is split into sentences
+    /// and, where needed, shorter chunks, see `segment`, and sentences are
+    /// separated by `sentence_pause_ms`. Invalid input is not synthesized,
+    /// and the first problem `validate_phonetic` finds is returned.
+    pub fn synthesize_phonetic(&self, input: &str) -> Result<Vec<u8>, Diagnostic> {
+        if let Some(diagnostic) = validate_phonetic(input).into_iter().next() {
+            return Err(diagnostic);
+        }
+        self.chunked(&self.params, input)
+    }
+
+    /// Synthesize with voice parameters that change over the utterance.
+    /// The input is converted at once, so it must fit `phoneme_rules`, see
+    /// `segment::fits`.
+    pub fn synthesize_phonetic_automated(&self, input: &str, automation: &Automation) -> Vec<u8> {
+        self.phonetic_with(&self.params, input, automation)
+    }
+
+    /// Synthesize phonemes that have already been converted, e.g. to
+    /// render lengths and pitches pinned with `Phonemes::pin_length` and
+    /// `Phonemes::pin_pitch`.
+    pub fn synthesize_phonemes(&self, phonemes: &Phonemes) -> Vec<u8> {
+        let frames = mk_frames_automated(&self.params, phonemes, &Automation::default());
+        self.render(&frames)
+    }
+
+    fn chunked(&self, params: &Params, input: &str) -> Result<Vec<u8>, Diagnostic> {
+        let pause = self.sentence_pause_ms as usize * self.output_sample_rate as usize / 1000;
+        let chunks = segment(input, &self.phoneme_rules)?;
+        let mut buffer = Vec::new();
+        for (idx, chunk) in chunks.iter().enumerate() {
+            buffer.extend(self.phonetic_with(params, &chunk.phonetic, &Automation::default()));
+            if chunk.boundary == Boundary::Sentence && idx + 1 < chunks.len() {
+                buffer.resize(buffer.len() + pause, SILENCE);
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn phonetic_with(&self, params: &Params, input: &str, automation: &Automation) -> Vec<u8> {
+        let phonemes = convert_phonemes_with(input.as_bytes(), &self.phoneme_rules);
+        let frames = mk_frames_automated(params, &phonemes, automation);
+        self.render(&frames)
+    }
+
+    /// Sing the lyrics of a Standard MIDI File to its melody. The lyric
+    /// events are read as phonetic input, one syllable per event.
+    pub fn sing_midi(&self, data: &[u8]) -> Result<Vec<u8>, MidiError> {
+        self.sing(&read_notes(data)?)
+    }
+
+    /// Sing notes with lyrics. Rests are filled with silence, so the
+    /// syllables start at the time of their first note.
+    pub fn sing(&self, notes: &[Note]) -> Result<Vec<u8>, MidiError> {
+        let sample_rate = self.output_sample_rate as f64;
+        let mut buffer = Vec::new();
+        for syllable in syllables(notes) {
+            let start = (syllable.start() * sample_rate).round() as usize;
+            if buffer.len() < start {
+                buffer.resize(start, SILENCE);
+            }
+            buffer.extend(self.render(&syllable_frames(&self.params, &syllable)?));
+        }
+        Ok(buffer)
+    }
+
+    fn render(&self, groups: &[FramesTables]) -> Vec<u8> {
+        match self.renderer {
+            Renderer::Classic => {
+                let buffer = mk_wav(groups);
+                resample_u8(&buffer, SAMPLE_RATE, self.output_sample_rate)
+            }
+            Renderer::Float => {
+                let buffer = groups
+                    .iter()
+                    .flat_map(|frames| remaster::render(frames, self.output_sample_rate))
+                    .collect::<Vec<_>>();
+                f32_to_u8(&buffer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        automation::Automation,
+        frames::SAMPLE_RATE,
+        midi::{MidiError, Note},
+        phoneme_rules::Breath,
+        sequence::Phoneme,
+        Params, Renderer, Synthesizer,
+    };
+
+    #[test]
+    fn hello_world() {
+        let mut synthesizer = Synthesizer::default();
+        assert!(!synthesizer.synthesize_text("hello world").is_empty());
+        assert!(!synthesizer.synthesize_phonetic("AA").unwrap().is_empty());
+        synthesizer.renderer = Renderer::Float;
+        synthesizer.output_sample_rate = 44100;
+        assert!(!synthesizer.synthesize_text("hello world").is_empty());
+    }
+
+    #[test]
+    fn long_text() {
+        // Each sentence is a chunk, and its audio is put together.
+        let synthesizer = Synthesizer::default();
+        let sentence = "It was the best of times, it was the worst of times. ";
+        let once = synthesizer.synthesize_text(sentence).len();
+        let text = synthesizer.synthesize_text(&sentence.repeat(4)).len();
+        assert!(text >= 4 * once);
+    }
+
+    #[test]
+    fn breaths() {
+        let text = "It was the best of times, it was the worst of times.";
+        let mut synthesizer = Synthesizer::default();
+        synthesizer.phoneme_rules.breath = None;
+        let without = synthesizer.synthesize_text(text).len();
+        synthesizer.phoneme_rules.breath = Some(Breath {
+            after_frames: 100,
+            ..Breath::audible()
+        });
+        assert!(synthesizer.synthesize_text(text).len() > without);
+    }
+
+    #[test]
+    fn sing() {
+        let note = |start: f64, end: f64, lyric: &str| Note {
+            start,
+            end,
+            key: 60,
+            lyric: Some(lyric.to_string()),
+        };
+        let synthesizer = Synthesizer::default();
+        let notes = [
+            note(0.0, 0.5, "/HEH"),
+            note(0.5, 1.0, "LOW"),
+            note(1.0, 10.0, "STRAA"),
+        ];
+        assert!(!synthesizer.sing(&notes).unwrap().is_empty());
+
+        for lyric in ["XQZ", &"STAA".repeat(30)] {
+            assert_eq!(
+                synthesizer.sing(&[note(0.0, 1.0, lyric)]),
+                Err(MidiError::InvalidLyric(lyric.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn longest_controls() {
+        // More frames than the frame tables of the C version hold.
+        let input = " AA{len=200} {pause=7s}{pause=7s} {pitch=30}AA{len=200} ";
+        let synthesizer = Synthesizer::default();
+        let seconds = |buffer: Vec<u8>| buffer.len() as f64 / SAMPLE_RATE as f64;
+        assert!(seconds(synthesizer.synthesize_phonetic(input).unwrap()) > 14.0);
+        let automated = synthesizer.synthesize_phonetic_automated(input, &Automation::default());
+        assert!(seconds(automated) > 14.0);
+    }
+
+    #[test]
+    fn first_frame_speed() {
+        let frame = |speed: u8| {
+            let params = Params {
+                speed,
+                ..Params::default()
+            };
+            Synthesizer::new(params)
+                .synthesize_phonetic("AA{len=1}")
+                .unwrap()
+                .len()
+        };
+        assert!(frame(144) > frame(72));
+    }
+
+    #[test]
+    fn invalid_phonetic() {
+        let mut synthesizer = Synthesizer::default();
+        let error = synthesizer.synthesize_phonetic("AA XQZ").unwrap_err();
+        assert_eq!(error.span, 3..5);
+
+        synthesizer.phoneme_rules.breath = Some(Breath {
+            after_frames: 100,
+            pause: Phoneme::new(31, 120, 0),
+            sound: None,
+        });
+        let error = synthesizer
+            .synthesize_phonetic(" AA AA{len=150} ")
+            .unwrap_err();
+        assert_eq!(error.span, 4..15);
+    }
+}
@@ -33,6 +33,15 @@ pub const RECTANGLE: [u8; 256] = [
     0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70,
     0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70, 0x70,
 ];
+// Output level for the 0 bits of unvoiced samples, indexed by sample table.
+//
+// T, S, Z                0          0x18
+// CH, J, SH, ZH          1          0x1A
+// P, F*, V, TH, DH       2          0x17
+// /H                     3          0x17
+// /X                     4          0x17
+pub const SAMPLE_OFF_LEVEL: [u8; 5] = [0x18, 0x1A, 0x17, 0x17, 0x17];
+
 pub const SAMPLE_TABLE: [u8; 1280] = [
     //00
     0x38, 0x84, 0x6B, 0x19, 0xC6, 0x63, 0x18, 0x86, 0x73, 0x98, 0xC6, 0xB1, 0x1C, 0xCA, 0x31, 0x8C,
@@ -0,0 +1,135 @@
+use std::fmt;
+
+// TRANSLITERATION
+//
+// The reciter's rules only know ASCII. Other letters are read as the
+// nearest ASCII letters, "café" as "cafe" and "Æsop" as "AEsop", unless
+// the rules spell them, as the Norwegian rules spell "ø". Typographic
+// punctuation is read as its ASCII counterpart: curly quotes as straight
+// quotes, dashes as hyphens, and the ellipsis as three periods. Special
+// spaces become spaces, and invisible characters such as the soft hyphen
+// are dropped.
+//
+// Anything else, like "☃" or "©", has no reading. It is skipped, and
+// `Reciter::unsupported` or `transliterate` report it.
+
+/// Lower case letters and their ASCII spelling. Upper case letters are
+/// looked up as lower case.
+#[rustfmt::skip]
+const LETTERS: [(char, &str); 98] = [
+    ('à', "a"), ('á', "a"), ('â', "a"), ('ã', "a"), ('ä', "a"), ('å', "a"), ('ā', "a"),
+    ('ă', "a"), ('ą', "a"), ('æ', "ae"),
+    ('ç', "c"), ('ć', "c"), ('ĉ', "c"), ('ċ', "c"), ('č', "c"),
+    ('ď', "d"), ('đ', "d"), ('ð', "d"),
+    ('è', "e"), ('é', "e"), ('ê', "e"), ('ë', "e"), ('ē', "e"), ('ĕ', "e"), ('ė', "e"),
+    ('ę', "e"), ('ě', "e"),
+    ('ĝ', "g"), ('ğ', "g"), ('ġ', "g"), ('ģ', "g"),
+    ('ĥ', "h"), ('ħ', "h"),
+    ('ì', "i"), ('í', "i"), ('î', "i"), ('ï', "i"), ('ĩ', "i"), ('ī', "i"), ('ĭ', "i"),
+    ('į', "i"), ('ı', "i"), ('ĳ', "ij"),
+    ('ĵ', "j"),
+    ('ķ', "k"),
+    ('ĺ', "l"), ('ļ', "l"), ('ľ', "l"), ('ŀ', "l"), ('ł', "l"),
+    ('ñ', "n"), ('ń', "n"), ('ņ', "n"), ('ň', "n"), ('ŋ', "ng"),
+    ('ò', "o"), ('ó', "o"), ('ô', "o"), ('õ', "o"), ('ö', "o"), ('ø', "o"), ('ō', "o"),
+    ('ŏ', "o"), ('ő', "o"), ('œ', "oe"),
+    ('ŕ', "r"), ('ŗ', "r"), ('ř', "r"),
+    ('ś', "s"), ('ŝ', "s"), ('ş', "s"), ('š', "s"), ('ș', "s"), ('ß', "ss"),
+    ('ţ', "t"), ('ť', "t"), ('ŧ', "t"), ('ț', "t"), ('þ', "th"),
+    ('ù', "u"), ('ú', "u"), ('û', "u"), ('ü', "u"), ('ũ', "u"), ('ū', "u"), ('ŭ', "u"),
+    ('ů', "u"), ('ű', "u"), ('ų', "u"),
+    ('ŵ', "w"),
+    ('ý', "y"), ('ÿ', "y"), ('ŷ', "y"),
+    ('ź', "z"), ('ż', "z"), ('ž', "z"),
+    ('ﬁ', "fi"), ('ﬂ', "fl"),
+];
+
+/// Typographic punctuation and spaces, and their ASCII counterparts.
+#[rustfmt::skip]
+const PUNCTUATION: [(char, &str); 34] = [
+    ('‘', "'"), ('’', "'"), ('‚', "'"), ('‛', "'"), ('′', "'"),
+    ('“', "\""), ('”', "\""), ('„', "\""), ('‟', "\""), ('″', "\""), ('«', "\""), ('»', "\""),
+    ('‐', "-"), ('‑', "-"), ('‒', "-"), ('–', "-"), ('−', "-"),
+    // Dashes between words are read as a pause.
+    ('—', " - "), ('―', " - "),
+    ('…', "..."),
+    ('¡', " "), ('¿', " "), ('•', " "), ('·', " "),
+    ('\u{a0}', " "), ('\u{2002}', " "), ('\u{2003}', " "), ('\u{2009}', " "),
+    ('\u{200a}', " "), ('\u{202f}', " "), ('\u{3000}', " "),
+    // Invisible: soft hyphen, zero width space and byte order mark.
+    ('\u{ad}', ""), ('\u{200b}', ""), ('\u{feff}', ""),
+];
+
+/// ASCII spelling of a letter or punctuation mark outside ASCII, in the
+/// case of the letter.
+pub fn transliterate_char(c: char) -> Option<String> {
+    let find = |c: char| {
+        LETTERS
+            .iter()
+            .chain(&PUNCTUATION)
+            .find(|(from, _)| *from == c)
+            .map(|(_, to)| *to)
+    };
+    if let Some(ascii) = find(c) {
+        return Some(ascii.to_string());
+    }
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) if lower != c => find(lower).map(str::to_uppercase),
+        _ => None,
+    }
+}
+
+/// A character without a reading, which is skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unsupported {
+    /// Byte offset in the text.
+    pub offset: usize,
+    pub symbol: char,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no reading for \"{}\" (U+{:04X}), skipped",
+            self.symbol, self.symbol as u32
+        )
+    }
+}
+
+/// The text in ASCII, with the characters that have no reading replaced
+/// by spaces and reported.
+pub fn transliterate(text: &str) -> (String, Vec<Unsupported>) {
+    let mut output = String::new();
+    let mut unsupported = Vec::new();
+    for (offset, c) in text.char_indices() {
+        if c.is_ascii() {
+            output.push(c);
+        } else if let Some(ascii) = transliterate_char(c) {
+            output.push_str(&ascii);
+        } else {
+            output.push(' ');
+            unsupported.push(Unsupported { offset, symbol: c });
+        }
+    }
+    (output, unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transliterate::{transliterate, Unsupported};
+
+    #[test]
+    fn ascii_spelling() {
+        let (text, unsupported) = transliterate("“Café” — naïve Æsop’s façade…\u{ad} ☃");
+        assert_eq!(text, "\"Cafe\"  -  naive AEsop's facade...  ");
+        assert_eq!(
+            unsupported,
+            [Unsupported {
+                offset: 46,
+                symbol: '☃'
+            }]
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::{fmt, ops::Range};
+
+use crate::phonemes::{phoneme_name, read_phoneme, Control, MAX_LENGTH, MAX_PAUSE_MS};
+
+// PHONETIC INPUT VALIDATION
+//
+// `convert_phonemes` panics on phonemes it can't read and skips controls
+// it can't read. `validate_phonetic` reads the input the same way and
+// reports every problem instead, with the byte range it applies to and
+// names that were probably meant, for editors to show next to the text.
+// Input without diagnostics converts and renders without panicking if it
+// fits, see `segment::fits`, down to empty input and punctuation with
+// nothing before it. The rules may make up to three phonemes out of one,
+// so the length of the input is left to `fits`, and the synthesizer
+// converts longer input in chunks.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Byte range of the problem in the input.
+    pub span: Range<usize>,
+    pub message: String,
+    /// Replacements for the text in `span`, best first.
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(span: Range<usize>, message: String) -> Self {
+        Self {
+            span,
+            message,
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn suggest(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.span.start)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Check phonetic input, e.g. "/HEH3LOW", before converting it.
+pub fn validate_phonetic(input: &str) -> Vec<Diagnostic> {
+    let bytes = input.as_bytes();
+    let mut diagnostics = Vec::new();
+    let mut phonemes = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let start = idx;
+        let sign = bytes[idx];
+
+        if sign == b'{' {
+            let Some(length) = bytes[idx..].iter().position(|c| *c == b'}') else {
+                diagnostics.push(Diagnostic::new(
+                    start..bytes.len(),
+                    "control is missing its closing `}`".to_string(),
+                ));
+                break;
+            };
+            idx += length + 1;
+            match Control::parse(&bytes[start + 1..idx - 1]) {
+                Some(Control::Pause(ms)) => {
+                    phonemes += 1;
+                    if ms > MAX_PAUSE_MS {
+                        diagnostics.push(Diagnostic::new(
+                            start..idx,
+                            format!(
+                                "pauses last at most {} ms, write a longer one as several",
+                                MAX_PAUSE_MS
+                            ),
+                        ));
+                    }
+                }
+                Some(Control::Length(_)) if phonemes == 0 => diagnostics.push(Diagnostic::new(
+                    start..idx,
+                    "length control with no phoneme before it".to_string(),
+                )),
+                Some(Control::Length(length)) if length > MAX_LENGTH => {
+                    diagnostics.push(Diagnostic::new(
+                        start..idx,
+                        format!("lengths go up to {} frames", MAX_LENGTH),
+                    ))
+                }
+                Some(_) => {}
+                None => diagnostics.push(
+                    Diagnostic::new(start..idx, "unknown control".to_string()).suggest(
+                        ["{pitch=64}", "{speed=72}", "{pause=300ms}", "{len=8}"]
+                            .map(String::from)
+                            .to_vec(),
+                    ),
+                ),
+            }
+            continue;
+        }
+
+        if sign == b'*' {
+            idx += bytes[idx..]
+                .iter()
+                .take_while(|c| **c == b'*')
+                .count()
+                .min(2);
+            diagnostics.push(Diagnostic::new(
+                start..idx,
+                "phoneme only used inside SAM, not in input".to_string(),
+            ));
+            continue;
+        }
+
+        // Lower case letters, e.g. "Sh" for "SH".
+        let upper = bytes[idx..(idx + 2).min(bytes.len())].to_ascii_uppercase();
+        if let Some((phoneme, length)) = read_phoneme(&upper) {
+            if bytes[idx..idx + length].iter().any(u8::is_ascii_lowercase) {
+                idx += length;
+                phonemes += 1;
+                let name = phoneme_name(phoneme).unwrap_or_default();
+                diagnostics.push(
+                    Diagnostic::new(
+                        start..idx,
+                        "phonemes are written in capital letters".to_string(),
+                    )
+                    .suggest(vec![name]),
+                );
+                continue;
+            }
+        }
+
+        if let Some((_, length)) = read_phoneme(&bytes[idx..]) {
+            idx += length;
+            phonemes += 1;
+            continue;
+        }
+
+        if sign.is_ascii_digit() {
+            idx += 1;
+            if !(b'1'..=b'8').contains(&sign) {
+                diagnostics.push(Diagnostic::new(
+                    start..idx,
+                    "stress digits go from 1 to 8".to_string(),
+                ));
+            } else if phonemes == 0 {
+                diagnostics.push(Diagnostic::new(
+                    start..idx,
+                    "stress digit with no phoneme before it".to_string(),
+                ));
+            }
+            continue;
+        }
+
+        if sign.is_ascii_whitespace() {
+            idx += 1;
+            diagnostics.push(
+                Diagnostic::new(start..idx, "words are separated by spaces".to_string())
+                    .suggest(vec![" ".to_string()]),
+            );
+            continue;
+        }
+
+        // Unknown, take two letters if they look like a name.
+        let length = match input[idx..].chars().next() {
+            Some(c) if !c.is_ascii() => c.len_utf8(),
+            _ if upper.len() == 2 && upper.iter().all(|c| is_name_sign(*c)) => 2,
+            _ => 1,
+        };
+        idx += length;
+        // Count it, so that its stress digit is not reported as well.
+        phonemes += 1;
+        let text = &input[start..idx];
+        let suggestions = closest_names(&text.to_ascii_uppercase());
+        let message = match suggestions.first() {
+            Some(name) => format!("unknown phoneme `{}`, did you mean {}?", text, name),
+            None => format!("unknown phoneme `{}`", text),
+        };
+        diagnostics.push(Diagnostic::new(start..idx, message).suggest(suggestions));
+    }
+    diagnostics
+}
+
+fn is_name_sign(sign: u8) -> bool {
+    sign.is_ascii_uppercase() || sign == b'/'
+}
+
+/// Phoneme names one edit away from `text`, or failing that two edits.
+fn closest_names(text: &str) -> Vec<String> {
+    let names = (0..=255)
+        .filter_map(phoneme_name)
+        .filter(|name| name.bytes().all(is_name_sign));
+    let mut scored = names
+        .map(|name| (edit_distance(text, &name), name))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect::<Vec<_>>();
+    let best = scored.iter().map(|(distance, _)| *distance).min();
+    scored.retain(|(distance, _)| Some(*distance) == best);
+    scored.into_iter().map(|(_, name)| name).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        phoneme_rules::PhonemeRuleSet, phonemes::convert_phonemes, segment::fits,
+        validate::validate_phonetic, Synthesizer,
+    };
+
+    #[test]
+    fn diagnostics() {
+        assert!(validate_phonetic("/HEH3LOW {pause=1s}WER4LD.").is_empty());
+        assert!(validate_phonetic("AA{len=200} {pause=7s}").is_empty());
+        assert_eq!(validate_phonetic("AA {pause=7.5s}")[0].span, 3..15);
+        assert_eq!(validate_phonetic("AA{len=201}")[0].span, 2..11);
+
+        let diagnostics = validate_phonetic("4Sh AI ** IY9{tone=3}");
+        let summary = diagnostics
+            .iter()
+            .map(|d| (d.span.clone(), d.suggestions.first().map(String::as_str)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (0..1, None),
+                (1..3, Some("SH")),
+                (4..6, Some("AE")),
+                (7..9, None),
+                (12..13, None),
+                (13..21, Some("{pitch=64}")),
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "unknown phoneme `AI`, did you mean AE?"
+        );
+        assert!(diagnostics[2].suggestions.contains(&"AY".to_string()));
+
+        // Rules look at the phoneme before the first one as well.
+        for input in ["SAY", "LOW", "RAY", "UW", "AA"] {
+            assert!(validate_phonetic(input).is_empty());
+            convert_phonemes(input.as_bytes());
+        }
+
+        // Nothing before the punctuation, or nothing at all.
+        for input in ["", ".", "?", " ?", "S.", ". AA."] {
+            assert!(validate_phonetic(input).is_empty());
+            convert_phonemes(input.as_bytes());
+            Synthesizer::default().synthesize_phonetic(input).unwrap();
+        }
+
+        // 'AY' becomes 'AY' 'YX'.
+        let rules = PhonemeRuleSet::default();
+        assert!(validate_phonetic(&"AY ".repeat(100)).is_empty());
+        assert!(!fits(&"AY ".repeat(100), &rules));
+        assert!(fits(&"AY ".repeat(10), &rules));
+        convert_phonemes("AY ".repeat(10).as_bytes());
+    }
+}
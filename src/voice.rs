@@ -0,0 +1,135 @@
+use std::{fmt, str::FromStr};
+
+use crate::Params;
+
+/// The character voices described in the original SAM manual.
+///
+/// ```text
+/// DESCRIPTION          SPEED     PITCH     THROAT    MOUTH
+/// Elf                   72        64        110       160
+/// Little Robot          92        60        190       190
+/// Stuffy Guy            82        72        110       105
+/// Little Old Lady       82        32        145       145
+/// Extra-Terrestrial    100        64        150       200
+/// SAM                   72        64        128       128
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoicePreset {
+    Sam,
+    Elf,
+    LittleRobot,
+    StuffyGuy,
+    LittleOldLady,
+    ExtraTerrestrial,
+}
+
+impl VoicePreset {
+    pub const ALL: [VoicePreset; 6] = [
+        VoicePreset::Sam,
+        VoicePreset::Elf,
+        VoicePreset::LittleRobot,
+        VoicePreset::StuffyGuy,
+        VoicePreset::LittleOldLady,
+        VoicePreset::ExtraTerrestrial,
+    ];
+
+    /// Name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoicePreset::Sam => "sam",
+            VoicePreset::Elf => "elf",
+            VoicePreset::LittleRobot => "little-robot",
+            VoicePreset::StuffyGuy => "stuffy-guy",
+            VoicePreset::LittleOldLady => "little-old-lady",
+            VoicePreset::ExtraTerrestrial => "extra-terrestrial",
+        }
+    }
+
+    pub fn params(&self) -> Params {
+        let (speed, pitch, throat, mouth) = match self {
+            VoicePreset::Sam => (72, 64, 128, 128),
+            VoicePreset::Elf => (72, 64, 110, 160),
+            VoicePreset::LittleRobot => (92, 60, 190, 190),
+            VoicePreset::StuffyGuy => (82, 72, 110, 105),
+            VoicePreset::LittleOldLady => (82, 32, 145, 145),
+            VoicePreset::ExtraTerrestrial => (100, 64, 150, 200),
+        };
+
+        Params {
+            speed,
+            pitch,
+            mouth,
+            throat,
+            ..Params::default()
+        }
+    }
+}
+
+impl fmt::Display for VoicePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<VoicePreset> for Params {
+    fn from(preset: VoicePreset) -> Self {
+        preset.params()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownVoice(pub String);
+
+impl fmt::Display for UnknownVoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown voice \"{}\", expected one of:", self.0)?;
+        for preset in VoicePreset::ALL {
+            write!(f, " {}", preset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownVoice {}
+
+impl FromStr for VoicePreset {
+    type Err = UnknownVoice;
+
+    /// Accepts the names in any case, with words separated by spaces,
+    /// hyphens or underscores, e.g. "Little Old Lady" or "little_old_lady".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase().replace([' ', '_'], "-");
+        VoicePreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == normalized)
+            .ok_or_else(|| UnknownVoice(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::voice::VoicePreset;
+
+    #[test]
+    fn parse_names() {
+        for preset in VoicePreset::ALL {
+            assert_eq!(preset.name().parse::<VoicePreset>(), Ok(preset));
+        }
+        assert_eq!("Little Old Lady".parse(), Ok(VoicePreset::LittleOldLady));
+        assert_eq!(
+            "EXTRA_TERRESTRIAL".parse(),
+            Ok(VoicePreset::ExtraTerrestrial)
+        );
+        assert!("robot".parse::<VoicePreset>().is_err());
+    }
+
+    #[test]
+    fn sam_is_default() {
+        let params = VoicePreset::Sam.params();
+        let default = crate::Params::default();
+        assert_eq!(
+            (params.speed, params.pitch, params.mouth, params.throat),
+            (default.speed, default.pitch, default.mouth, default.throat)
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+/// Write 8-bit unsigned mono samples as a RIFF WAVE file.
+pub fn write_wav<W: Write>(mut writer: W, buffer: &[u8], sample_rate: u32) -> io::Result<()> {
+    let data_length = buffer.len() as u32;
+
+    // RIFF header
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(data_length + 12 + 16 + 8 - 8).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    // format chunk
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&1u16.to_le_bytes())?; // channels
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?; // bytes/second
+    writer.write_all(&1u16.to_le_bytes())?; // block align
+    writer.write_all(&8u16.to_le_bytes())?; // bits per sample
+
+    // data chunk
+    writer.write_all(b"data")?;
+    writer.write_all(&data_length.to_le_bytes())?;
+    writer.write_all(buffer)?;
+
+    writer.flush()
+}
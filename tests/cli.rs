@@ -0,0 +1,34 @@
+use std::process::{Command, Output};
+
+// Tests that run the `sam` binary the way
+// it is used from a shell.
+
+fn sam(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sam-rs"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn writes_wav() {
+    let path = std::env::temp_dir().join(format!("sam-cli-{}.wav", std::process::id()));
+    let output = sam(&["-wav", path.to_str().unwrap(), "hello", "world"]);
+    assert!(output.status.success());
+
+    let wav = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(&wav[..4], b"RIFF");
+    assert!(wav.len() > 44);
+}
+
+#[test]
+fn explains() {
+    let output = sam(&["explain", "hello"]);
+    assert!(output.status.success());
+
+    // Only the tables, without debug output before them.
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("TEXT"));
+    assert!(stdout.contains("<VOWEL> L -> <VOWEL> LX"));
+}
@@ -1,12 +1,44 @@
-use serial_test::serial;
+use sam_rs::{phonemes::convert_phonemes, Synthesizer};
 
-/// Tests that verify the exact compatibility
-/// with the original C code of SAM, which was
-/// reconstructed from the Commodore 64 binary.
+// Tests that verify the exact compatibility
+// with the original C code of SAM, which was
+// reconstructed from the Commodore 64 binary.
+//
+// The files in `tests/data` were written by the `sam` program built from
+// `sam_sys/libsam`, e.g. `sam -phonetic -wav out.wav " DHAX KWIHK ..."`,
+// without the 44 bytes of the WAV header.
 
+fn recorded(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/data/{}.raw", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(path).unwrap()
+}
 
 #[test]
-#[serial]
-pub fn test() {
-    
-}
\ No newline at end of file
+pub fn hello_world() {
+    let buffer = Synthesizer::default().synthesize_text("hello world");
+    assert!(buffer == recorded("hello_world"));
+}
+
+#[test]
+pub fn sentences() {
+    // Converted at once, like the C version, rather than sentence by
+    // sentence as `synthesize_phonetic` does. The first input is rendered
+    // in a breath group after each punctuation mark, the second takes a
+    // breath after 232 frames, and the space in the third is lengthened
+    // before the period but still not rendered.
+    for (name, phonetic) in [
+        (
+            "my_name_is_sam",
+            " /HEHLOW, MAY NEYM IHZ SAEM. IHZ DHIHS DHAX ZUW5? ",
+        ),
+        (
+            "quick_brown_fox",
+            " DHAX KWIHK BROWN FAAKS JAH5MS OW5VER DHAX LEY4ZIY DAAG ",
+        ),
+        ("lengthened_space", " AA, S. "),
+    ] {
+        let phonemes = convert_phonemes(phonetic.as_bytes());
+        let buffer = Synthesizer::default().synthesize_phonemes(&phonemes);
+        assert!(buffer == recorded(name), "{}", name);
+    }
+}